@@ -0,0 +1,90 @@
+use macroquad::math::Vec2;
+use crate::{Graph, NodeKey, NodeGetSet};
+
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Alignment {Left, Center, Right, Top, Middle, Bottom}
+
+
+pub fn align_nodes(graph: &mut Graph, keys: &[NodeKey], alignment: Alignment) {
+    if keys.is_empty() { return; }
+
+    let vs = keys.iter().map(|&k| graph.nodes[k].get_p_v()).collect::<Vec<Vec2>>();
+    let min = vs.iter().fold(vs[0], |acc, &v| acc.min(v));
+    let max = vs.iter().fold(vs[0], |acc, &v| acc.max(v));
+    let mid = (min + max) / 2.0;
+
+    for (&n_k, &v) in keys.iter().zip(&vs) {
+        let v = match alignment {
+            Alignment::Left => Vec2::new(min.x, v.y),
+            Alignment::Center => Vec2::new(mid.x, v.y),
+            Alignment::Right => Vec2::new(max.x, v.y),
+            Alignment::Top => Vec2::new(v.x, min.y),
+            Alignment::Middle => Vec2::new(v.x, mid.y),
+            Alignment::Bottom => Vec2::new(v.x, max.y)
+        };
+        graph.set_node_pos(n_k, &v);
+    }
+}
+
+
+// keeps the two outermost nodes in place and spaces the rest evenly between them
+pub fn distribute_nodes(graph: &mut Graph, keys: &[NodeKey], horizontal: bool) {
+    if keys.len() < 3 { return; }
+
+    let axis = |v: Vec2| if horizontal { v.x } else { v.y };
+    let mut sorted = keys
+        .iter()
+        .map(|&k| (k, graph.nodes[k].get_p_v()))
+        .collect::<Vec<(NodeKey, Vec2)>>();
+    sorted.sort_by(|a, b| axis(a.1).total_cmp(&axis(b.1)));
+
+    let first = axis(sorted[0].1);
+    let last = axis(sorted[sorted.len() - 1].1);
+    let step = (last - first) / (sorted.len() - 1) as f32;
+
+    for (i, &(n_k, v)) in sorted.iter().enumerate() {
+        let p = first + step * i as f32;
+        let v = match horizontal {
+            true => Vec2::new(p, v.y),
+            false => Vec2::new(v.x, p)
+        };
+        graph.set_node_pos(n_k, &v);
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use super::*;
+
+    fn positions(graph: &Graph, keys: &[NodeKey]) -> Vec<Vec2> {
+        keys.iter().map(|&k| graph.nodes[k].get_p_v()).collect()
+    }
+
+    #[test]
+    fn align() {
+        let (mut graph, n, _e) = test_graph(&[(0.0, 5.0), (30.0, -5.0), (10.0, 20.0)], &[], false);
+        align_nodes(&mut graph, &n, Alignment::Right);
+        assert_eq!(positions(&graph, &n), vec![Vec2::new(30.0, 5.0), Vec2::new(30.0, -5.0), Vec2::new(30.0, 20.0)]);
+
+        align_nodes(&mut graph, &n, Alignment::Middle);
+        assert!(positions(&graph, &n).iter().all(|v| v.y == 7.5));
+    }
+
+    #[test]
+    fn distribute_keeps_the_ends() {
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (40.0, 3.0), (5.0, 0.0), (30.0, 0.0)], &[], false);
+        distribute_nodes(&mut graph, &n, true);
+        let x = positions(&graph, &n).iter().map(|v| v.x).collect::<Vec<f32>>();
+        assert_eq!(x, vec![0.0, 40.0, 40.0 / 3.0, 80.0 / 3.0]);
+        assert_eq!(graph.nodes[n[1]].get_p_v().y, 3.0);
+
+        // fewer than three nodes have nothing in between
+        distribute_nodes(&mut graph, &n[..2], false);
+        assert_eq!(graph.nodes[n[1]].get_p_v().y, 3.0);
+    }
+}
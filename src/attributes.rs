@@ -0,0 +1,173 @@
+use std::fmt;
+use macroquad::color::Color;
+use serde::{Serialize, Deserialize};
+use crate::BTreeMap;
+
+
+
+// untagged, so plain json values ("text", 1.5, true, [r, g, b, a]) are read back as is
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum AttrValue {
+    Bool(bool),
+    Number(f32),
+    Text(String),
+    Color([f32; 4])
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AttrType {Text, Number, Bool, Color}
+
+pub type Attributes = BTreeMap<String, AttrValue>;
+
+
+impl AttrType {
+    pub const ALL: [AttrType; 4] = [AttrType::Text, AttrType::Number, AttrType::Bool, AttrType::Color];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttrType::Text => "text",
+            AttrType::Number => "number",
+            AttrType::Bool => "bool",
+            AttrType::Color => "color"
+        }
+    }
+
+    pub fn default_value(&self) -> AttrValue {
+        match self {
+            AttrType::Text => AttrValue::Text(String::new()),
+            AttrType::Number => AttrValue::Number(0.0),
+            AttrType::Bool => AttrValue::Bool(false),
+            AttrType::Color => AttrValue::Color([1.0, 1.0, 1.0, 1.0])
+        }
+    }
+}
+
+
+impl AttrValue {
+    pub fn get_type(&self) -> AttrType {
+        match self {
+            AttrValue::Text(_) => AttrType::Text,
+            AttrValue::Number(_) => AttrType::Number,
+            AttrValue::Bool(_) => AttrType::Bool,
+            AttrValue::Color(_) => AttrType::Color
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f32> {
+        match self {
+            AttrValue::Number(x) => Some(*x),
+            AttrValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            AttrValue::Text(s) => s.trim().parse().ok(),
+            AttrValue::Color(_) => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AttrValue::Bool(b) => Some(*b),
+            _ => None
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            AttrValue::Text(s) => Some(s.as_str()),
+            _ => None
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            AttrValue::Color(c) => Some(Color::new(c[0], c[1], c[2], c[3])),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for AttrValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttrValue::Text(s) => write!(f, "{}", s),
+            AttrValue::Number(x) => write!(f, "{}", x),
+            AttrValue::Bool(b) => write!(f, "{}", b),
+            AttrValue::Color(c) => write!(f, "#{:02x}{:02x}{:02x}{:02x}",
+                                          (c[0] * 255.0) as u8, (c[1] * 255.0) as u8, (c[2] * 255.0) as u8, (c[3] * 255.0) as u8)
+        }
+    }
+}
+
+
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AttrTarget {Node, Edge}
+
+
+// attribute definitions of the graph, the default value also fixes the type
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AttrSchema {
+    pub node: Attributes,
+    pub edge: Attributes
+}
+
+impl AttrSchema {
+    pub fn defs(&self, target: AttrTarget) -> &Attributes {
+        match target {
+            AttrTarget::Node => &self.node,
+            AttrTarget::Edge => &self.edge
+        }
+    }
+
+    pub fn defs_mut(&mut self, target: AttrTarget) -> &mut Attributes {
+        match target {
+            AttrTarget::Node => &mut self.node,
+            AttrTarget::Edge => &mut self.edge
+        }
+    }
+
+    // undefined attributes accept any type
+    pub fn accepts(&self, target: AttrTarget, name: &str, value: &AttrValue) -> bool {
+        match self.defs(target).get(name) {
+            Some(def) => def.get_type() == value.get_type(),
+            None => true
+        }
+    }
+
+    pub fn numeric(&self, target: AttrTarget) -> Vec<String> {
+        self.defs(target)
+            .iter()
+            .filter(|(_name, def)| def.get_type() == AttrType::Number)
+            .map(|(name, _def)| name.clone())
+            .collect()
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_json_values() {
+        let values = [AttrValue::Text("a".to_string()), AttrValue::Number(1.5), AttrValue::Bool(true), AttrValue::Color([1.0, 0.0, 0.0, 1.0])];
+        let json = serde_json::to_string(&values).unwrap();
+
+        assert_eq!(json, r#"["a",1.5,true,[1.0,0.0,0.0,1.0]]"#);
+        assert_eq!(serde_json::from_str::<Vec<AttrValue>>(&json).unwrap(), values);
+    }
+
+    #[test]
+    fn schema_types() {
+        let mut schema = AttrSchema::default();
+        schema.node.insert("weight".to_string(), AttrValue::Number(1.0));
+        schema.node.insert("label".to_string(), AttrValue::Text(String::new()));
+
+        assert!(schema.accepts(AttrTarget::Node, "weight", &AttrValue::Number(2.0)));
+        assert!(!schema.accepts(AttrTarget::Node, "weight", &AttrValue::Text("2".to_string())));
+        assert!(schema.accepts(AttrTarget::Edge, "weight", &AttrValue::Bool(true)));
+        assert_eq!(schema.numeric(AttrTarget::Node), vec!["weight".to_string()]);
+        assert_eq!(AttrValue::Text(" 2 ".to_string()).as_number(), Some(2.0));
+    }
+}
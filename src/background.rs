@@ -0,0 +1,134 @@
+use macroquad::color::*;
+use macroquad::math::Vec2;
+use macroquad::shapes::{draw_circle, draw_line};
+use macroquad::texture::{draw_texture_ex, DrawTextureParams, Texture2D};
+use serde::{Serialize, Deserialize};
+
+
+
+pub const CALIBRATION_COLOR: Color = MAGENTA;
+pub const CALIBRATION_POINT_RADIUS: f32 = 4.0;
+pub const CALIBRATION_DISTANCE: f32 = 100.0;
+
+
+// placement of the background map, saved with the graph
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Background {
+    #[serde(default)]
+    pub path: Option<String>,
+    // world position of the top left corner of the image
+    pub offset: [f32; 2],
+    // world units per image pixel
+    pub scale: f32,
+    // degrees, clockwise around the top left corner
+    pub rotation: f32,
+    pub opacity: f32,
+    // set by the two-point calibration
+    #[serde(default)]
+    pub meters_per_unit: Option<f32>
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self {
+            path: None,
+            offset: [0.0, 0.0],
+            scale: 1.0,
+            rotation: 0.0,
+            opacity: 1.0,
+            meters_per_unit: None
+        }
+    }
+}
+
+
+impl Background {
+    pub fn get_offset(&self) -> Vec2 {
+        Vec2::from(self.offset)
+    }
+
+    // world positions of the image corners, clockwise from the top left one
+    pub fn corners(&self, texture: &Texture2D) -> [Vec2; 4] {
+        let o = self.get_offset();
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let x = Vec2::new(cos, sin) * texture.width() * self.scale;
+        let y = Vec2::new(-sin, cos) * texture.height() * self.scale;
+        [o, o + x, o + x + y, o + y]
+    }
+
+    pub fn bounds(&self, texture: &Texture2D) -> (Vec2, Vec2) {
+        let c = self.corners(texture);
+        let min = c.iter().fold(c[0], |acc, &v| acc.min(v));
+        let max = c.iter().fold(c[0], |acc, &v| acc.max(v));
+        (min, max)
+    }
+
+    // `to_screen` maps world positions and `k` world lengths, so the minimap can reuse it
+    pub fn draw_mapped(&self, texture: &Texture2D, to_screen: impl Fn(Vec2) -> Vec2, k: f32) {
+        let v = to_screen(self.get_offset());
+        draw_texture_ex(*texture, v.x, v.y, Color::new(1.0, 1.0, 1.0, self.opacity), DrawTextureParams {
+            dest_size: Some(Vec2::new(texture.width(), texture.height()) * self.scale * k),
+            rotation: self.rotation.to_radians(),
+            pivot: Some(v),
+            ..Default::default()
+        });
+    }
+
+    pub fn draw(&self, texture: &Texture2D) {
+        self.draw_mapped(texture, |v| v, 1.0);
+    }
+
+    // the real distance between two clicked world points sets the meters per world unit
+    pub fn calibrate(&mut self, a: Vec2, b: Vec2, meters: f32) -> bool {
+        let d = a.distance(b);
+        match d > 0.0 && meters > 0.0 {
+            true => {
+                self.meters_per_unit = Some(meters / d);
+                true
+            }
+            false => false
+        }
+    }
+}
+
+
+pub fn draw_calibration(points: &[Vec2], cursor: Option<Vec2>) {
+    let mut points = points.to_vec();
+    if let Some(v) = cursor {
+        if points.len() < 2 { points.push(v); }
+    }
+    if let [a, b] = points[..] {
+        draw_line(a.x, a.y, b.x, b.y, 2.0, CALIBRATION_COLOR);
+    }
+    for v in points {
+        draw_circle(v.x, v.y, CALIBRATION_POINT_RADIUS, CALIBRATION_COLOR);
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calibration() {
+        let mut background = Background::default();
+        assert!(background.calibrate(Vec2::ZERO, Vec2::new(30.0, 40.0), 100.0));
+        assert_eq!(background.meters_per_unit, Some(2.0));
+
+        assert!(!background.calibrate(Vec2::ONE, Vec2::ONE, 100.0));
+        assert!(!background.calibrate(Vec2::ZERO, Vec2::ONE, 0.0));
+        assert_eq!(background.meters_per_unit, Some(2.0));
+    }
+
+    #[test]
+    fn older_files_have_no_path_or_calibration() {
+        let json = r#"{"offset": [1.0, 2.0], "scale": 0.5, "rotation": 0.0, "opacity": 1.0}"#;
+        let background = serde_json::from_str::<Background>(json).unwrap();
+
+        assert_eq!(background.get_offset(), Vec2::new(1.0, 2.0));
+        assert_eq!(background.path, None);
+        assert_eq!(background.meters_per_unit, None);
+    }
+}
@@ -1,11 +1,13 @@
-use crate::{EdgePos, EdgeKeys, EdgeKey, NodeKey};
+use crate::{EdgePos, EdgeKeys, EdgeKey, NodeKey, Attributes, EdgeStyle, EDGE_THICKNESS, EDGE_COLOR, to_color};
 use serde::{Deserialize, Serialize};
 use macroquad::{
     color::*,
     prelude::{
-        draw_line
+        draw_line,
+        draw_circle
     }
 };
+use std::f32::consts::PI;
 use macroquad::math::Vec2;
 use macroquad::shapes::draw_triangle;
 
@@ -44,22 +46,47 @@ pub(crate) trait EdgeGraph {
 
 
 
-#[derive(Serialize, Deserialize)]
-pub struct Edge {
-    pub(crate) pos: EdgePos,
-    pub keys: Option<EdgeKeys>
+// control and bend points are stored relative to the edge endpoints as
+// [fraction along the chord, offset to the left of it], so they follow the nodes
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+pub enum EdgeShape {
+    #[default]
+    Straight,
+    Curved([f32; 2]),
+    Polyline(Vec<[f32; 2]>)
 }
 
-impl Default for Edge {
-    fn default() -> Self {
-        Self {
-            pos: EdgePos::default(),
-            keys: None
+impl EdgeShape {
+    // the same line seen from the other end, for an edge with swapped endpoints
+    pub fn reversed(&self) -> Self {
+        let flip = |l: &[f32; 2]| [1.0 - l[0], -l[1]];
+        match self {
+            EdgeShape::Straight => EdgeShape::Straight,
+            EdgeShape::Curved(l) => EdgeShape::Curved(flip(l)),
+            EdgeShape::Polyline(bends) => EdgeShape::Polyline(bends.iter().rev().map(flip).collect())
         }
     }
 }
 
 
+#[derive(Serialize, Deserialize, Default)]
+pub struct Edge {
+    pub(crate) pos: EdgePos,
+    pub keys: Option<EdgeKeys>,
+    #[serde(default)]
+    pub(crate) shape: EdgeShape,
+    #[serde(default)]
+    pub(crate) attributes: Attributes,
+    #[serde(default, skip_serializing_if = "EdgeStyle::is_empty")]
+    pub(crate) style: EdgeStyle,
+    #[serde(skip)]
+    pub(crate) resolved: EdgeStyle,
+    // radii of the tail and head nodes, so styled nodes are not drawn over
+    #[serde(skip)]
+    pub(crate) end_r: Option<[f32; 2]>
+}
+
+
 
 impl Edge {
     pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
@@ -73,47 +100,352 @@ impl Edge {
     pub fn from(p1: Vec2, p2: Vec2) -> Self {
         Self {
             pos: EdgePos::new(p1.x, p1.y, p2.x, p2.y),
-            keys: None
+            ..Edge::default()
         }
     }
 
 
-    pub fn draw(&self, t: f32, col: Color) {
+    pub fn get_shape(&self) -> &EdgeShape {
+        &self.shape
+    }
+
+    pub fn set_shape(&mut self, shape: EdgeShape) {
+        self.shape = shape;
+    }
+
+    pub fn get_attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    pub fn get_style(&self) -> &EdgeStyle {
+        &self.style
+    }
+
+    pub fn get_resolved_style(&self) -> &EdgeStyle {
+        &self.resolved
+    }
+
+    fn thickness_scale(&self) -> f32 {
+        self.resolved.thickness.map_or(1.0, |t| t / EDGE_THICKNESS)
+    }
+
+    fn to_world(&self, l: [f32; 2]) -> Vec2 {
+        chord_to_world(self.get_p1_v(), self.get_p2_v(), l)
+    }
+
+    fn to_local(&self, v: Vec2) -> [f32; 2] {
+        chord_to_local(self.get_p1_v(), self.get_p2_v(), v)
+    }
+
+    // the drawn line from the tail to the head center, curves are sampled
+    pub fn path(&self) -> Vec<Vec2> {
         let v1 = self.get_p1_v();
         let v2 = self.get_p2_v();
-        let v = v2 - v1;
-        let vn = v.normalize();
-        let vpn = vn.perp();
 
-        let vl1 = v1 + vn*R;
-        let vl2 = v2 - vn*(R + AT);
-        draw_line(vl1.x, vl1.y, vl2.x, vl2.y, t, col);
-        draw_triangle(vl2 + vn* AT, vl2 + vpn* AT, vl2 - vpn* AT, col);
+        match &self.shape {
+            EdgeShape::Straight => vec![v1, v2],
+            EdgeShape::Curved(l) => {
+                let c = self.to_world(*l);
+                (0..=CURVE_SEGMENTS)
+                    .map(|i| {
+                        let s = i as f32 / CURVE_SEGMENTS as f32;
+                        v1 * (1.0 - s).powi(2) + c * 2.0 * s * (1.0 - s) + v2 * s.powi(2)
+                    })
+                    .collect()
+            }
+            EdgeShape::Polyline(bends) => {
+                let mut points = vec![v1];
+                points.extend(bends.iter().map(|&l| self.to_world(l)));
+                points.push(v2);
+                points
+            }
+        }
+    }
 
+    pub fn handles(&self) -> Vec<Vec2> {
+        match &self.shape {
+            EdgeShape::Straight => Vec::new(),
+            EdgeShape::Curved(l) => vec![self.to_world(*l)],
+            EdgeShape::Polyline(bends) => bends.iter().map(|&l| self.to_world(l)).collect()
+        }
     }
 
+    pub fn set_handle(&mut self, i: usize, v: Vec2) {
+        let l = self.to_local(v);
+        match &mut self.shape {
+            EdgeShape::Straight => {}
+            EdgeShape::Curved(c) => *c = l,
+            EdgeShape::Polyline(bends) => if let Some(b) = bends.get_mut(i) { *b = l; }
+        }
+    }
 
+    pub fn remove_handle(&mut self, i: usize) {
+        match &mut self.shape {
+            EdgeShape::Polyline(bends) if bends.len() > 1 => if i < bends.len() { bends.remove(i); }
+            _ => self.shape = EdgeShape::Straight
+        }
+    }
 
-    pub fn draw_selected(&self, t: f32, ts: f32, col: Color) {
+    // index of the path segment nearest to `v`
+    fn nearest_segment(path: &[Vec2], v: Vec2) -> usize {
+        (0..path.len() - 1)
+            .min_by(|&a, &b| {
+                let da = point_segment_distance(v, path[a], path[a + 1]);
+                let db = point_segment_distance(v, path[b], path[b + 1]);
+                da.total_cmp(&db)
+            })
+            .unwrap_or(0)
+    }
+
+    // the bend is inserted into the segment nearest to `v`
+    pub fn add_bend(&mut self, v: Vec2) {
+        let l = self.to_local(v);
+        let i = Self::nearest_segment(&self.path(), v);
+
+        match &mut self.shape {
+            EdgeShape::Polyline(bends) => bends.insert(i, l),
+            _ => self.shape = EdgeShape::Polyline(vec![l])
+        }
+    }
+
+    // point at `d` along the path from its start and the path direction there
+    fn point_along(path: &[Vec2], d: f32) -> (Vec2, Vec2) {
+        let mut left = d.max(0.0);
+        for w in path.windows(2) {
+            let len = w[0].distance(w[1]);
+            if left <= len && len > 0.0 {
+                let dir = (w[1] - w[0]) / len;
+                return (w[0] + dir * left, dir);
+            }
+            left -= len;
+        }
+        let n = path.len();
+        (path[n - 1], (path[n - 1] - path[n - 2]).normalize_or_zero())
+    }
+
+    // part of the path between the distances `a` and `b` from its start
+    fn sub_path(path: &[Vec2], a: f32, b: f32) -> Vec<Vec2> {
+        let mut points = vec![Self::point_along(path, a).0];
+        let mut walked = 0.0;
+        for w in path.windows(2) {
+            walked += w[0].distance(w[1]);
+            if a < walked && walked < b { points.push(w[1]); }
+        }
+        points.push(Self::point_along(path, b).0);
+        points
+    }
+
+    // shapes of the parts from the tail to `v` and from `v` to the head when
+    // the edge is split at `v`, which is expected to lie on the path
+    pub fn split_shape(&self, v: Vec2) -> (EdgeShape, EdgeShape) {
+        let v1 = self.get_p1_v();
+        let v2 = self.get_p2_v();
+
+        match &self.shape {
+            EdgeShape::Straight => (EdgeShape::Straight, EdgeShape::Straight),
+            EdgeShape::Curved(l) => {
+                // de Casteljau at the curve parameter nearest to `v`
+                let path = self.path();
+                let i = Self::nearest_segment(&path, v);
+                let (a, b) = (path[i], path[i + 1]);
+                let t = match (b - a).length_squared() {
+                    l if l > 0.0 => ((v - a).dot(b - a) / l).clamp(0.0, 1.0),
+                    _ => 0.0
+                };
+                let s = (i as f32 + t) / CURVE_SEGMENTS as f32;
+                let c = self.to_world(*l);
+                let c1 = v1.lerp(c, s);
+                let c2 = c.lerp(v2, s);
+                (EdgeShape::Curved(chord_to_local(v1, v, c1)), EdgeShape::Curved(chord_to_local(v, v2, c2)))
+            }
+            EdgeShape::Polyline(_) => {
+                let path = self.path();
+                let i = Self::nearest_segment(&path, v);
+                let bends = |points: &[Vec2], a: Vec2, b: Vec2| match points.is_empty() {
+                    true => EdgeShape::Straight,
+                    false => EdgeShape::Polyline(points.iter().map(|&p| chord_to_local(a, b, p)).collect())
+                };
+                (bends(&path[1..=i], v1, v), bends(&path[i + 1..path.len() - 1], v, v2))
+            }
+        }
+    }
+
+    pub fn midpoint(&self) -> (Vec2, Vec2) {
+        Self::point_along(&self.path(), self.get_length() / 2.0)
+    }
+
+    fn draw_path(&self, t: f32, at: f32, td: f32, col: Color) {
+        let path = self.path();
+        let len = self.get_length();
+        let [r1, r2] = self.end_r.unwrap_or([R, R]);
+        let end = len - (r2 + AT * self.thickness_scale().max(1.0) + td);
+
+        let pieces = match self.resolved.dash {
+            Some([dash, gap]) if dash > 0.0 => {
+                let mut pieces = Vec::new();
+                let mut d = r1;
+                while d < end {
+                    pieces.push(Self::sub_path(&path, d, (d + dash).min(end)));
+                    d += dash + gap.max(0.0);
+                }
+                pieces
+            }
+            _ => vec![Self::sub_path(&path, r1, end)]
+        };
+        for line in pieces {
+            for w in line.windows(2) {
+                draw_line(w[0].x, w[0].y, w[1].x, w[1].y, t, col);
+            }
+        }
+
+        let (vl2, vn) = Self::point_along(&path, end);
+        let vpn = vn.perp();
+        draw_triangle(vl2 + vn*at, vl2 + vpn*at, vl2 - vpn*at, col);
+    }
+
+    pub fn draw_handles(&self, col: Color) {
         let v1 = self.get_p1_v();
         let v2 = self.get_p2_v();
-        let v = v2 - v1;
-        let vn = v.normalize();
+        let handles = self.handles();
+
+        if let EdgeShape::Curved(_) = self.shape {
+            for h in &handles {
+                draw_line(v1.x, v1.y, h.x, h.y, 1.0, col);
+                draw_line(v2.x, v2.y, h.x, h.y, 1.0, col);
+            }
+        }
+        for h in handles {
+            draw_circle(h.x, h.y, HANDLE_RADIUS, col);
+        }
+    }
+
+
+    // edges not added to a graph yet are loops when their ends coincide
+    pub fn is_loop(&self) -> bool {
+        match &self.keys {
+            Some(keys) => keys.from == keys.to,
+            None => self.get_p1_v() == self.get_p2_v()
+        }
+    }
+
+    // loop circle center and the angles where it leaves and enters the node
+    fn loop_geometry(&self) -> (Vec2, f32, f32) {
+        let v = self.get_p1_v();
+        let u = Vec2::new(0.0, -1.0);
+        let d = R + LOOP_R * 0.5;
+        let c = v + u * d;
+        let a = (R * R - LOOP_R * LOOP_R + d * d) / (2.0 * d);
+        let alpha = ((d - a) / LOOP_R).acos();
+        let phi = f32::atan2(-u.y, -u.x);
+
+        (c, phi + alpha, phi + 2.0 * PI - alpha)
+    }
+
+    fn draw_loop(&self, t: f32, at: f32, col: Color) {
+        let (c, s, e) = self.loop_geometry();
+        let e_line = e - at / LOOP_R;
+        let arc = |angle: f32| c + Vec2::new(angle.cos(), angle.sin()) * LOOP_R;
+
+        let mut prev = arc(s);
+        for i in 1..=LOOP_SEGMENTS {
+            let next = arc(s + (e_line - s) * i as f32 / LOOP_SEGMENTS as f32);
+            draw_line(prev.x, prev.y, next.x, next.y, t, col);
+            prev = next;
+        }
+
+        let tip = arc(e);
+        let vn = (tip - prev).normalize();
         let vpn = vn.perp();
+        draw_triangle(prev + vn * at, prev + vpn * at, prev - vpn * at, col);
+    }
+
+    // distance from `v` to the drawn line, None if `v` is beyond the segment ends
+    pub fn distance_to(&self, v: Vec2) -> Option<f32> {
+        if self.is_loop() {
+            let (c, _s, _e) = self.loop_geometry();
+            return Some((c.distance(v) - LOOP_R).abs());
+        }
+
+        let path = self.path();
+        let segments = path
+            .windows(2)
+            .filter_map(|w| {
+                let v12 = w[1] - w[0];
+                let v10 = v - w[0];
+                let proj_unnorm = v10.dot(v12);
+
+                match 0.0 <= proj_unnorm && proj_unnorm <= v12.length_squared() && v12 != Vec2::ZERO {
+                    true => Some(v10.perp_dot(v12.normalize()).abs()),
+                    false => None
+                }
+            });
+        // inner corners are covered by neither of their segments
+        let corners = path[1..path.len() - 1].iter().map(|p| p.distance(v));
+
+        segments.chain(corners).reduce(f32::min)
+    }
+
+    // `t` is meant for an edge of the default thickness and is scaled with the styled one
+    pub fn draw(&self, t: f32, col: Color) {
+        let k = self.thickness_scale();
+        let at = AT * k.max(1.0);
+        if self.is_loop() { return self.draw_loop(t * k, at, col); }
+        self.draw_path(t * k, at, 0.0, col);
+    }
+
+    pub fn draw_styled(&self, alpha: f32) {
+        let mut col = self.resolved.color.map_or(EDGE_COLOR, to_color);
+        col.a *= alpha;
+        self.draw(EDGE_THICKNESS, col);
+    }
 
+
+
+    pub fn draw_selected(&self, t: f32, ts: f32, col: Color) {
+        let k = self.thickness_scale();
+        let (t, ts) = (t * k, ts * k);
         let td = (ts - t)/2.0;
-        let ats = AT + 2.41*td;
-        let vl1 = v1 + vn*R;
-        let vl2 = v2 - vn*(R + AT + td);
-        draw_line(vl1.x, vl1.y, vl2.x, vl2.y, ts, col);
-        draw_triangle(vl2 + vn*ats, vl2 + vpn*ats, vl2 - vpn*ats, col);
+        let ats = AT * k.max(1.0) + 2.41*td;
+        if self.is_loop() { return self.draw_loop(ts, ats, col); }
+        self.draw_path(ts, ats, td, col);
+    }
+
+}
+
+
+// points relative to the chord from `a` to `b` as [fraction along it, offset to its left]
+fn chord_to_world(a: Vec2, b: Vec2, l: [f32; 2]) -> Vec2 {
+    let d = b - a;
+    a + d * l[0] + d.normalize_or_zero().perp() * l[1]
+}
+
+fn chord_to_local(a: Vec2, b: Vec2, v: Vec2) -> [f32; 2] {
+    let d = b - a;
+    let w = v - a;
+    match d.length_squared() {
+        l if l > 0.0 => [w.dot(d) / l, w.dot(d.normalize().perp())],
+        _ => [0.0, 0.0]
     }
+}
+
 
+fn point_segment_distance(v: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = match ab.length_squared() {
+        l if l > 0.0 => ((v - a).dot(ab) / l).clamp(0.0, 1.0),
+        _ => 0.0
+    };
+    v.distance(a + ab * t)
 }
 
 
 const R: f32 = 10.0;
 const AT: f32 = 10.0;
+const LOOP_R: f32 = 12.0;
+const LOOP_SEGMENTS: usize = 24;
+const CURVE_SEGMENTS: usize = 24;
+pub const HANDLE_RADIUS: f32 = 4.0;
+pub const CURVE_OFFSET: f32 = 40.0;
 
 impl EdgeGetSet for Edge {
     fn set_p1(&mut self, x: f32, y: f32) {
@@ -164,7 +496,10 @@ impl EdgeGetSet for Edge {
     }
 
     fn get_length(&self) -> f32 {
-        Vec2::distance(self.get_p1_v(), self.get_p2_v())
+        self.path()
+            .windows(2)
+            .map(|w| Vec2::distance(w[0], w[1]))
+            .sum()
     }
 
     fn get_keys(&self) -> &EdgeKeys {
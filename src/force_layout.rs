@@ -0,0 +1,159 @@
+use macroquad::color::*;
+use macroquad::math::Vec2;
+use slotmap::SecondaryMap;
+use crate::{Graph, NodeKey, NodeGetSet, EdgeGetSet, HashSet};
+
+
+
+pub const IDEAL_EDGE_LENGTH: f32 = 60.0;
+pub const COOLING: f32 = 0.98;
+pub const GRAVITY: f32 = 0.05;
+pub const MIN_TEMPERATURE: f32 = 0.5;
+
+pub const PINNED_NODE_RADIUS: f32 = 9.0;
+pub const PINNED_NODE_COLOR: Color = DARKGRAY;
+
+
+// Fruchterman–Reingold layout, advanced one iteration per `step`
+// so the editor can animate it frame by frame
+pub struct ForceLayout {
+    pub ideal_length: f32,
+    pub temperature: f32,
+    pub pinned: HashSet<NodeKey>,
+    pub running: bool
+}
+
+
+impl ForceLayout {
+    pub fn new(ideal_length: f32) -> Self {
+        Self {
+            ideal_length,
+            temperature: ideal_length,
+            pinned: HashSet::new(),
+            running: false
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.temperature = self.ideal_length;
+    }
+
+    pub fn converged(&self) -> bool {
+        self.temperature < MIN_TEMPERATURE
+    }
+
+    pub fn toggle_pin(&mut self, key: NodeKey) {
+        if !self.pinned.remove(&key) {
+            self.pinned.insert(key);
+        }
+    }
+
+    // keys of removed nodes would otherwise pile up in the pinned set
+    pub fn retain_existing(&mut self, graph: &Graph) {
+        self.pinned.retain(|&k| graph.nodes.contains_key(k));
+    }
+
+    pub fn step(&mut self, graph: &mut Graph, held: Option<NodeKey>) {
+        let n = graph.nodes.len();
+        if n == 0 { return; }
+
+        let k = self.ideal_length;
+        let keys = graph.nodes.keys().collect::<Vec<NodeKey>>();
+        let pos = graph.nodes
+            .iter()
+            .map(|(n_k, node)| (n_k, node.get_p_v()))
+            .collect::<SecondaryMap<NodeKey, Vec2>>();
+
+        let centroid = pos.values().fold(Vec2::ZERO, |acc, &v| acc + v) / n as f32;
+        let mut disp: SecondaryMap<NodeKey, Vec2> = keys
+            .iter()
+            .map(|&n_k| (n_k, (centroid - pos[n_k]) * GRAVITY))
+            .collect();
+
+        for (i, &a) in keys.iter().enumerate() {
+            for (j, &b) in keys.iter().enumerate().skip(i + 1) {
+                let mut d = pos[a] - pos[b];
+                if d.length_squared() < 1e-4 {
+                    // spread coincident nodes apart in a reproducible direction
+                    let angle = (i * 31 + j * 17) as f32;
+                    d = Vec2::new(angle.cos(), angle.sin()) * 0.01;
+                }
+                let force = d.normalize() * k * k / d.length();
+                disp[a] += force;
+                disp[b] -= force;
+            }
+        }
+
+        for e in graph.edges.values() {
+            let keys = e.get_keys();
+            if keys.from == keys.to { continue; }
+
+            let d = pos[keys.to] - pos[keys.from];
+            let force = d * d.length() / k;
+            disp[keys.from] += force;
+            disp[keys.to] -= force;
+        }
+
+        for n_k in keys {
+            if self.pinned.contains(&n_k) || held == Some(n_k) { continue; }
+
+            let d = disp[n_k];
+            let len = d.length();
+            if len > 0.0 {
+                let v = pos[n_k] + d / len * len.min(self.temperature);
+                graph.set_node_pos(n_k, &v);
+            }
+        }
+
+        self.temperature *= COOLING;
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use super::*;
+
+    #[test]
+    fn pinned_and_held_nodes_stay() {
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (10.0, 5.0), (20.0, 0.0)], &[(0, 1), (1, 2)], true);
+        let mut layout = ForceLayout::new(IDEAL_EDGE_LENGTH);
+        layout.toggle_pin(n[0]);
+        for _ in 0..10 {
+            layout.step(&mut graph, Some(n[2]));
+        }
+
+        assert_eq!(graph.nodes[n[0]].get_p_v(), Vec2::new(0.0, 0.0));
+        assert_eq!(graph.nodes[n[2]].get_p_v(), Vec2::new(20.0, 0.0));
+        assert_ne!(graph.nodes[n[1]].get_p_v(), Vec2::new(10.0, 5.0));
+
+        layout.toggle_pin(n[0]);
+        assert!(layout.pinned.is_empty());
+    }
+
+    #[test]
+    fn converges_near_the_ideal_length() {
+        let (mut graph, n, _e) = path_graph(2);
+        let mut layout = ForceLayout::new(IDEAL_EDGE_LENGTH);
+        while !layout.converged() {
+            layout.step(&mut graph, None);
+        }
+
+        let d = graph.nodes[n[0]].get_p_v().distance(graph.nodes[n[1]].get_p_v());
+        assert!((d - IDEAL_EDGE_LENGTH).abs() < IDEAL_EDGE_LENGTH * 0.25, "{}", d);
+    }
+
+    #[test]
+    fn removed_nodes_are_unpinned() {
+        let (mut graph, n, _e) = path_graph(3);
+        let mut layout = ForceLayout::new(IDEAL_EDGE_LENGTH);
+        layout.toggle_pin(n[0]);
+        layout.toggle_pin(n[1]);
+
+        graph.remove_node(n[1]);
+        layout.retain_existing(&graph);
+        assert_eq!(layout.pinned, HashSet::from([n[0]]));
+    }
+}
@@ -0,0 +1,265 @@
+use std::f32::consts::PI;
+use macroquad::math::Vec2;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use crate::{Graph, Node, Edge, NodeKey, EdgeKey, NodeGetSet, ForceLayout, HashSet};
+
+
+
+pub const GENERATOR_SPACING: f32 = 60.0;
+pub const GENERATOR_LAYOUT_STEPS: usize = 200;
+// node pairs evaluated by the relaxation, bigger graphs get fewer steps
+pub const GENERATOR_LAYOUT_BUDGET: usize = 20_000_000;
+pub const GENERATOR_MIN_LAYOUT_STEPS: usize = 20;
+
+pub const GEN_NODES: usize = 30;
+pub const GEN_P: f32 = 0.1;
+pub const GEN_M: usize = 2;
+pub const GEN_K: usize = 4;
+pub const GEN_BETA: f32 = 0.2;
+pub const GEN_SEED: u64 = 0;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum GraphModel {ErdosRenyi, BarabasiAlbert, WattsStrogatz}
+
+
+
+// nodes are placed on a circle around the origin, pairs are unordered
+fn build_graph(n: usize, pairs: &[(usize, usize)], undirected: bool) -> Graph {
+    let mut graph = Graph::new();
+    let r = (GENERATOR_SPACING * n as f32 / (2.0 * PI)).max(GENERATOR_SPACING);
+
+    let keys = (0..n)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f32 / n as f32 - PI / 2.0;
+            graph.add_node(Node::from(Vec2::new(angle.cos(), angle.sin()) * r))
+        })
+        .collect::<Vec<NodeKey>>();
+
+    for &(a, b) in pairs {
+        connect(&mut graph, keys[a], keys[b], undirected);
+    }
+    graph
+}
+
+
+pub fn connect(graph: &mut Graph, a: NodeKey, b: NodeKey, undirected: bool) -> Vec<EdgeKey> {
+    let va = graph.nodes[a].get_p_v();
+    let vb = graph.nodes[b].get_p_v();
+
+    let mut added = vec![graph.add_edge(Edge::from(va, vb), a, b)];
+    if undirected { added.push(graph.add_edge(Edge::from(vb, va), b, a)); }
+    added
+}
+
+
+// runs synchronously, graphs too big for a few steps keep the circle
+fn relax(graph: &mut Graph) {
+    let n = graph.nodes.len();
+    let steps = GENERATOR_LAYOUT_STEPS.min(GENERATOR_LAYOUT_BUDGET / (n * n).max(1));
+    if steps < GENERATOR_MIN_LAYOUT_STEPS { return; }
+
+    let mut layout = ForceLayout::new(GENERATOR_SPACING);
+    for _ in 0..steps {
+        layout.step(graph, None);
+        if layout.converged() { break; }
+    }
+}
+
+
+pub fn erdos_renyi(n: usize, p: f32, undirected: bool, seed: u64) -> Graph {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pairs = Vec::new();
+
+    for a in 0..n {
+        for b in a + 1..n {
+            if rng.gen::<f32>() < p { pairs.push((a, b)); }
+        }
+    }
+
+    let mut graph = build_graph(n, &pairs, undirected);
+    relax(&mut graph);
+    graph
+}
+
+
+pub fn barabasi_albert(n: usize, m: usize, undirected: bool, seed: u64) -> Graph {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let m = m.max(1).min(n.saturating_sub(1).max(1));
+    let mut pairs = Vec::new();
+
+    // every node appears here once per incident edge, so uniform picks are degree-proportional
+    let mut repeated = Vec::new();
+    let mut targets = (0..m).collect::<Vec<usize>>();
+
+    for source in m..n {
+        for &t in &targets {
+            pairs.push((t, source));
+        }
+        repeated.extend(&targets);
+        repeated.extend(vec![source; m]);
+
+        let mut chosen = HashSet::new();
+        while chosen.len() < m {
+            chosen.insert(*repeated.choose(&mut rng).unwrap());
+        }
+        targets = chosen.into_iter().collect();
+        targets.sort();
+    }
+
+    let mut graph = build_graph(n, &pairs, undirected);
+    relax(&mut graph);
+    graph
+}
+
+
+pub fn watts_strogatz(n: usize, k: usize, beta: f32, undirected: bool, seed: u64) -> Graph {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let half = (k / 2).min(n.saturating_sub(1) / 2);
+    let norm = |a: usize, b: usize| (a.min(b), a.max(b));
+
+    let mut pairs = Vec::new();
+    for j in 1..=half {
+        for a in 0..n {
+            pairs.push((a, (a + j) % n));
+        }
+    }
+    // the pairs keep their source node first so it stays the rewired end
+    let mut present = pairs.iter().map(|&(a, b)| norm(a, b)).collect::<HashSet<(usize, usize)>>();
+
+    for pair in pairs.iter_mut() {
+        if rng.gen::<f32>() >= beta { continue; }
+
+        let (a, b) = *pair;
+        let free = (0..n)
+            .filter(|&w| w != a && !present.contains(&norm(a, w)))
+            .collect::<Vec<usize>>();
+
+        if let Some(&w) = free.choose(&mut rng) {
+            present.remove(&norm(a, b));
+            present.insert(norm(a, w));
+            *pair = (a, w);
+        }
+    }
+
+    // the ring is already a sensible layout for small-world graphs
+    build_graph(n, &pairs, undirected)
+}
+
+
+
+// one attempt of the `N` key: a random point around a random node is accepted
+// if it is not too close to any node and not too far from all of them
+pub fn spawn_node<R: Rng>(graph: &mut Graph, rng: &mut R, no_neighbour_dist: f32, max_neighbour_dist: f32) -> Option<NodeKey> {
+    let n_k = graph.nodes.keys().choose(rng)?;
+    let n_v = graph.nodes[n_k].get_p_v();
+
+    let rv = n_v + Vec2::new((rng.gen::<f32>() - 0.5) * 2.0 * max_neighbour_dist,
+                             (rng.gen::<f32>() - 0.5) * 2.0 * max_neighbour_dist);
+    match graph.find_nodes(rv, 0.0, no_neighbour_dist).is_empty() &&
+        !graph.find_nodes(rv, 0.0, max_neighbour_dist).is_empty() {
+        true => Some(graph.add_node(Node::from(rv))),
+        false => None
+    }
+}
+
+
+// one attempt of the `E` key: joins a random node with a random node
+// lying within the edge length bounds
+pub fn spawn_edge<R: Rng>(graph: &mut Graph, rng: &mut R, min_length: f32, max_length: f32) -> Option<EdgeKey> {
+    let n_k = graph.nodes.keys().choose(rng)?;
+    let n_v = graph.nodes[n_k].get_p_v();
+
+    let &rn_k = graph.find_nodes(n_v, min_length, max_length).choose(rng)?;
+    let rn_v = graph.nodes[rn_k].get_p_v();
+
+    Some(graph.add_edge(Edge::from(n_v, rn_v), n_k, rn_k))
+}
+
+
+pub fn grow_nodes(graph: &mut Graph, attempts: usize, no_neighbour_dist: f32, max_neighbour_dist: f32, seed: u64) -> Vec<NodeKey> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..attempts)
+        .filter_map(|_| spawn_node(graph, &mut rng, no_neighbour_dist, max_neighbour_dist))
+        .collect::<Vec<NodeKey>>()
+}
+
+
+pub fn grow_edges(graph: &mut Graph, attempts: usize, min_length: f32, max_length: f32, seed: u64) -> Vec<EdgeKey> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..attempts)
+        .filter_map(|_| spawn_edge(graph, &mut rng, min_length, max_length))
+        .collect::<Vec<EdgeKey>>()
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::EdgeGetSet;
+    use super::*;
+
+    #[test]
+    fn erdos_renyi_edge_counts() {
+        assert_eq!(erdos_renyi(10, 0.0, true, GEN_SEED).edges.len(), 0);
+        assert_eq!(erdos_renyi(10, 1.0, false, GEN_SEED).edges.len(), 45);
+        assert_eq!(erdos_renyi(10, 1.0, true, GEN_SEED).edges.len(), 90);
+
+        // the same seed gives the same graph
+        let a = erdos_renyi(40, GEN_P, false, 7).edges.len();
+        assert_eq!(a, erdos_renyi(40, GEN_P, false, 7).edges.len());
+    }
+
+    #[test]
+    fn barabasi_albert_edge_counts() {
+        // every node after the first m brings m edges
+        assert_eq!(barabasi_albert(30, 2, false, GEN_SEED).edges.len(), 56);
+        assert_eq!(barabasi_albert(30, 3, true, 5).edges.len(), 2 * 81);
+    }
+
+    #[test]
+    fn watts_strogatz_edge_counts() {
+        // rewiring never drops or duplicates a ring edge
+        for beta in [0.0, GEN_BETA, 1.0] {
+            assert_eq!(watts_strogatz(20, 4, beta, false, GEN_SEED).edges.len(), 40);
+        }
+        assert_eq!(watts_strogatz(20, 4, GEN_BETA, true, GEN_SEED).edges.len(), 80);
+    }
+
+    #[test]
+    fn watts_strogatz_rewires_the_target() {
+        // every node keeps the k / 2 edges it is the source of, the wrapping ones included
+        let graph = watts_strogatz(20, 4, 1.0, false, GEN_SEED);
+        assert!(graph.nodes.values().all(|node| node.get_keys().tails.len() == 2));
+    }
+
+    #[test]
+    fn growth_is_seeded_and_bounded() {
+        let grow = |seed: u64| {
+            let mut graph = erdos_renyi(3, 0.0, false, GEN_SEED);
+            let nodes = grow_nodes(&mut graph, 200, 20.0, 60.0, seed);
+            let edges = grow_edges(&mut graph, 50, 20.0, 80.0, seed);
+            (graph, nodes, edges)
+        };
+        let (graph, nodes, edges) = grow(3);
+        assert_eq!((nodes.len(), edges.len()), (grow(3).1.len(), grow(3).2.len()));
+        assert!(!nodes.is_empty() && !edges.is_empty());
+
+        // new nodes keep their distance to the others and new edges their length
+        for &n_k in &nodes {
+            let v = graph.nodes[n_k].get_p_v();
+            assert!(graph.nodes.iter().all(|(k, o)| k == n_k || o.get_p_v().distance(v) >= 20.0));
+        }
+        assert!(edges.iter().all(|&e_k| (20.0..80.0).contains(&graph.edges[e_k].get_length())));
+    }
+
+    #[test]
+    fn big_graphs_keep_the_circle() {
+        let n = 1500;
+        let r = GENERATOR_SPACING * n as f32 / (2.0 * PI);
+        let graph = erdos_renyi(n, 0.0, false, GEN_SEED);
+
+        assert!(graph.nodes.values().all(|node| (node.get_p_v().length() - r).abs() < 1e-2 * r));
+    }
+}
@@ -0,0 +1,323 @@
+use macroquad::math::{Vec2, DVec2};
+use crate::{Graph, NodeKey, EdgeKey, NodeGetSet, HashSet, connect};
+
+
+
+pub const NEAREST_NEIGHBOURS: usize = 3;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Proximity {Delaunay, Gabriel, RelativeNeighbourhood, Nearest}
+
+
+
+struct Triangle {
+    v: [usize; 3],
+    center: DVec2,
+    r2: f64
+}
+
+impl Triangle {
+    fn new(v: [usize; 3], pts: &[DVec2]) -> Option<Self> {
+        let (a, b, c) = (pts[v[0]], pts[v[1]], pts[v[2]]);
+        let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+        if d.abs() < 1e-12 { return None; }
+
+        let (a2, b2, c2) = (a.length_squared(), b.length_squared(), c.length_squared());
+        let center = DVec2::new(
+            (a2 * (b.y - c.y) + b2 * (c.y - a.y) + c2 * (a.y - b.y)) / d,
+            (a2 * (c.x - b.x) + b2 * (a.x - c.x) + c2 * (b.x - a.x)) / d
+        );
+        Some(Self { v, center, r2: center.distance_squared(a) })
+    }
+}
+
+
+// returns unordered index pairs, coincident points are joined through their first index only
+// and collinear points, which have no triangles, are chained along their line
+pub fn delaunay(points: &[Vec2]) -> Vec<(usize, usize)> {
+    let mut seen = HashSet::new();
+    let unique = (0..points.len())
+        .filter(|&i| seen.insert((points[i].x.to_bits(), points[i].y.to_bits())))
+        .collect::<Vec<usize>>();
+    let pts = unique.iter().map(|&i| points[i].as_dvec2()).collect::<Vec<DVec2>>();
+
+    let pairs = match collinear_order(&pts) {
+        Some(order) => order.windows(2).map(|w| (w[0], w[1])).collect(),
+        None => bowyer_watson(pts)
+    };
+    let mut pairs = pairs
+        .into_iter()
+        .map(|(a, b)| (unique[a].min(unique[b]), unique[a].max(unique[b])))
+        .collect::<Vec<(usize, usize)>>();
+    pairs.sort();
+    pairs
+}
+
+
+// indices sorted along the line if all points lie on one, fewer than three always do
+fn collinear_order(pts: &[DVec2]) -> Option<Vec<usize>> {
+    let Some(&a) = pts.first() else { return Some(Vec::new()); };
+    let b = pts.iter().copied().max_by(|p, q| a.distance_squared(*p).total_cmp(&a.distance_squared(*q)))?;
+    let d = b - a;
+    if pts.iter().any(|&p| d.perp_dot(p - a).abs() > 1e-9 * d.length_squared()) { return None; }
+
+    let mut order = (0..pts.len()).collect::<Vec<usize>>();
+    order.sort_by(|&i, &j| d.dot(pts[i] - a).total_cmp(&d.dot(pts[j] - a)));
+    Some(order)
+}
+
+
+// distinct points not all on one line
+fn bowyer_watson(mut pts: Vec<DVec2>) -> Vec<(usize, usize)> {
+    let n = pts.len();
+    let min = pts.iter().fold(pts[0], |acc, &v| acc.min(v));
+    let max = pts.iter().fold(pts[0], |acc, &v| acc.max(v));
+    let mid = (min + max) / 2.0;
+    let size = (max - min).max_element().max(1.0) * 20.0;
+    pts.push(mid + DVec2::new(-size, -size));
+    pts.push(mid + DVec2::new(size, -size));
+    pts.push(mid + DVec2::new(0.0, size));
+
+    let mut triangles = vec![Triangle::new([n, n + 1, n + 2], &pts).unwrap()];
+
+    for p in 0..n {
+        let v = pts[p];
+        let (bad, good): (Vec<Triangle>, Vec<Triangle>) = triangles
+            .into_iter()
+            .partition(|t| t.center.distance_squared(v) < t.r2);
+        triangles = good;
+
+        let mut boundary: Vec<(usize, usize)> = Vec::new();
+        for t in &bad {
+            for i in 0..3 {
+                let e = (t.v[i].min(t.v[(i + 1) % 3]), t.v[i].max(t.v[(i + 1) % 3]));
+                match boundary.iter().position(|&b| b == e) {
+                    Some(j) => { boundary.swap_remove(j); }
+                    None => boundary.push(e)
+                }
+            }
+        }
+
+        for (a, b) in boundary {
+            if let Some(t) = Triangle::new([a, b, p], &pts) {
+                triangles.push(t);
+            }
+        }
+    }
+
+    let mut pairs = HashSet::new();
+    for t in triangles.iter().filter(|t| t.v.iter().all(|&i| i < n)) {
+        for i in 0..3 {
+            let (a, b) = (t.v[i], t.v[(i + 1) % 3]);
+            pairs.insert((a.min(b), a.max(b)));
+        }
+    }
+    pairs.into_iter().collect()
+}
+
+
+// delaunay edges whose diametral circle is empty
+pub fn gabriel(points: &[Vec2]) -> Vec<(usize, usize)> {
+    delaunay(points)
+        .into_iter()
+        .filter(|&(a, b)| {
+            let mid = (points[a] + points[b]) / 2.0;
+            let r2 = points[a].distance_squared(mid);
+            points
+                .iter()
+                .enumerate()
+                .all(|(c, v)| c == a || c == b || v.distance_squared(mid) >= r2)
+        })
+        .collect()
+}
+
+
+// delaunay edges with no third point closer to both ends than they are to each other
+pub fn relative_neighbourhood(points: &[Vec2]) -> Vec<(usize, usize)> {
+    delaunay(points)
+        .into_iter()
+        .filter(|&(a, b)| {
+            let d2 = points[a].distance_squared(points[b]);
+            points
+                .iter()
+                .enumerate()
+                .all(|(c, v)| c == a || c == b || v.distance_squared(points[a]).max(v.distance_squared(points[b])) >= d2)
+        })
+        .collect()
+}
+
+
+// joins every node to its k nearest nodes among `keys` lying within the length bounds,
+// returns the keys of the joined edges
+pub fn connect_nearest(graph: &mut Graph, keys: &[NodeKey], k: usize, min_length: f32, max_length: f32, undirected: bool) -> Vec<EdgeKey> {
+    let allowed = keys.iter().copied().collect::<HashSet<NodeKey>>();
+    let mut added = Vec::new();
+
+    for &n_k in keys {
+        let n_v = graph.nodes[n_k].get_p_v();
+        let mut near = graph.find_nodes(n_v, min_length, max_length)
+            .into_iter()
+            .filter(|nn_k| allowed.contains(nn_k))
+            .map(|nn_k| (nn_k, graph.nodes[nn_k].get_p_v().distance_squared(n_v)))
+            .collect::<Vec<(NodeKey, f32)>>();
+        near.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        for (nn_k, _d) in near.into_iter().take(k) {
+            added.extend(connect(graph, n_k, nn_k, undirected));
+        }
+    }
+    added
+}
+
+
+pub fn connect_proximity(graph: &mut Graph, keys: &[NodeKey], proximity: Proximity, k: usize, min_length: f32, max_length: f32, undirected: bool) -> Vec<EdgeKey> {
+    if proximity == Proximity::Nearest {
+        return connect_nearest(graph, keys, k, min_length, max_length, undirected);
+    }
+
+    let points = keys.iter().map(|&n_k| graph.nodes[n_k].get_p_v()).collect::<Vec<Vec2>>();
+    let pairs = match proximity {
+        Proximity::Delaunay => delaunay(&points),
+        Proximity::Gabriel => gabriel(&points),
+        Proximity::RelativeNeighbourhood => relative_neighbourhood(&points),
+        Proximity::Nearest => unreachable!()
+    };
+
+    let mut added = Vec::new();
+    for (a, b) in pairs {
+        let d = points[a].distance(points[b]);
+        if min_length <= d && d <= max_length {
+            added.extend(connect(graph, keys[a], keys[b], undirected));
+        }
+    }
+    added
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+    use crate::testing::*;
+    use super::*;
+
+    fn random_points(n: usize, seed: u64) -> Vec<Vec2> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|_| Vec2::new(rng.gen_range(0.0..500.0), rng.gen_range(0.0..500.0)))
+            .collect()
+    }
+
+    #[test]
+    fn delaunay_small() {
+        let triangle = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(5.0, 8.0)];
+        assert_eq!(delaunay(&triangle), vec![(0, 1), (0, 2), (1, 2)]);
+
+        // a point inside is joined to every corner
+        let mut points = triangle.to_vec();
+        points.push(Vec2::new(5.0, 3.0));
+        assert_eq!(delaunay(&points), vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn delaunay_degenerate() {
+        assert_eq!(delaunay(&[]), vec![]);
+        assert_eq!(delaunay(&[Vec2::new(1.0, 2.0)]), vec![]);
+        assert_eq!(delaunay(&[Vec2::new(0.0, 0.0), Vec2::new(3.0, 4.0)]), vec![(0, 1)]);
+
+        // collinear points are chained in their order along the line
+        let line = [3.0, 0.0, 2.0, 1.0].map(|t| Vec2::new(t, 2.0 * t));
+        assert_eq!(delaunay(&line), vec![(0, 2), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn delaunay_duplicates() {
+        let points = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(0.0, 0.0), Vec2::new(5.0, 8.0), Vec2::new(10.0, 0.0)];
+        assert_eq!(delaunay(&points), vec![(0, 1), (0, 3), (1, 3)]);
+
+        let same = [Vec2::new(1.0, 1.0); 3];
+        assert_eq!(delaunay(&same), vec![]);
+        assert_eq!(delaunay(&[Vec2::ZERO, Vec2::ZERO, Vec2::new(1.0, 0.0)]), vec![(0, 2)]);
+    }
+
+    #[test]
+    fn delaunay_circumcircles_are_empty() {
+        let points = random_points(40, 1);
+        let pts = points.iter().map(|v| v.as_dvec2()).collect::<Vec<DVec2>>();
+        let pairs = delaunay(&points).into_iter().collect::<HashSet<(usize, usize)>>();
+        let has = |a: usize, b: usize| pairs.contains(&(a.min(b), a.max(b)));
+        let side = |a: DVec2, b: DVec2, v: DVec2| (b - a).perp_dot(v - a) > 0.0;
+
+        // planar, so at most 3n - 6 edges
+        assert!(pairs.len() <= 3 * points.len() - 6);
+
+        let n = points.len();
+        let mut faces = 0;
+        for a in 0..n {
+            for b in a + 1..n {
+                for c in b + 1..n {
+                    if !(has(a, b) && has(b, c) && has(a, c)) { continue; }
+                    let (va, vb, vc) = (pts[a], pts[b], pts[c]);
+                    let others = || (0..n).filter(|&i| i != a && i != b && i != c);
+
+                    // a cycle of three edges with nothing inside is a face
+                    let inside = others().any(|i| {
+                        let v = pts[i];
+                        let s = side(va, vb, v);
+                        s == side(vb, vc, v) && s == side(vc, va, v)
+                    });
+                    if inside { continue; }
+
+                    let t = Triangle::new([a, b, c], &pts).unwrap();
+                    assert!(others().all(|i| t.center.distance_squared(pts[i]) >= t.r2 * (1.0 - 1e-9)));
+                    faces += 1;
+                }
+            }
+        }
+        // 2n - 2 - h faces with h hull points
+        assert!(faces >= n - 2);
+    }
+
+    #[test]
+    fn gabriel_drops_blocked_edges() {
+        let points = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(5.0, 1.0)];
+        assert_eq!(gabriel(&points), vec![(0, 2), (1, 2)]);
+        assert_eq!(relative_neighbourhood(&points), vec![(0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn proximity_graphs_are_nested() {
+        let points = random_points(60, 2);
+        let delaunay = delaunay(&points).into_iter().collect::<HashSet<(usize, usize)>>();
+        let gabriel = gabriel(&points).into_iter().collect::<HashSet<(usize, usize)>>();
+        let rng = relative_neighbourhood(&points).into_iter().collect::<HashSet<(usize, usize)>>();
+
+        assert!(rng.is_subset(&gabriel));
+        assert!(gabriel.is_subset(&delaunay));
+        // the relative neighbourhood graph of points in general position is connected
+        assert!(rng.len() >= points.len() - 1);
+    }
+
+    #[test]
+    fn connect_within_bounds() {
+        // 0 - 1 - 2 on a line, 3 far away
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (30.0, 0.0), (200.0, 0.0)], &[], false);
+        let added = connect_proximity(&mut graph, &n, Proximity::Delaunay, 0, 0.0, 50.0, true);
+        assert_eq!(added.len(), 4);
+        assert!(joined(&graph, n[1], n[2]) && joined(&graph, n[2], n[1]));
+        assert_eq!(graph.degree(n[3]), 0);
+    }
+
+    #[test]
+    fn connect_nearest_neighbours() {
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (30.0, 0.0), (200.0, 0.0)], &[], false);
+        let added = connect_nearest(&mut graph, &n[..3], 1, 0.0, 500.0, false);
+
+        // 0 -> 1, 1 -> 0 and 2 -> 1, the node outside `keys` is left alone
+        assert_eq!(added.len(), 3);
+        assert!(joined(&graph, n[2], n[1]));
+        assert_eq!(graph.degree(n[3]), 0);
+    }
+}
@@ -1,6 +1,6 @@
 use std::f32::consts::PI;
-use macroquad::color::{colors::*, rgb_to_hsl, hsl_to_rgb};
-use crate::{Node, Edge, NodeGetSet, EdgeGetSet};
+use macroquad::color::{Color, colors::*, rgb_to_hsl, hsl_to_rgb};
+use crate::{Node, Edge, EdgeShape, AttrSchema, AttrTarget, AttrValue, Attributes, StyleRule, NodeStyle, EdgeStyle, DIM_ALPHA, Background, Units, NodeGetSet, EdgeGetSet, HashSet, HANDLE_RADIUS, CURVE_OFFSET};
 use macroquad::math::Vec2;
 use macroquad::text::{draw_text_ex, TextParams};
 use slotmap::{new_key_type, SlotMap};
@@ -17,13 +17,39 @@ new_key_type! {
     pub struct EdgeKey;
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum SL {None, Node(NodeKey), Edge(EdgeKey)}
 
+pub const HANDLE_COLOR: Color = DARKBLUE;
+
 
 #[derive(Serialize, Deserialize)]
 pub struct Graph {
     pub nodes: SlotMap<NodeKey, Node>,
-    pub edges: SlotMap<EdgeKey, Edge>
+    pub edges: SlotMap<EdgeKey, Edge>,
+    #[serde(default)]
+    pub schema: AttrSchema,
+    #[serde(default)]
+    style_rules: Vec<StyleRule>,
+    #[serde(default)]
+    pub background: Background,
+    #[serde(default)]
+    pub units: Units,
+    // topology, positions and attributes, style changes are counted apart
+    #[serde(skip)]
+    revision: u64,
+    #[serde(skip)]
+    style_changes: u64,
+    // both counters when the styles were last resolved
+    #[serde(skip)]
+    style_revision: Option<(u64, u64)>
+}
+
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 
@@ -31,11 +57,23 @@ impl Graph {
     pub fn new() -> Self {
         Self {
             nodes: SlotMap::with_key(),
-            edges: SlotMap::with_key()
+            edges: SlotMap::with_key(),
+            schema: AttrSchema::default(),
+            style_rules: Vec::new(),
+            background: Background::default(),
+            units: Units::default(),
+            revision: 0,
+            style_changes: 0,
+            style_revision: None
         }
     }
 
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     pub fn add_node(&mut self, mut node: Node) -> NodeKey {
+        self.revision += 1;
         self.nodes.insert_with_key(|k| {
             node.graph_init(k);
             node
@@ -53,6 +91,7 @@ impl Graph {
             }
         }
 
+        self.revision += 1;
         let key = self.edges.insert_with_key(|k| {
             edge.graph_init(k, tail_key, head_key);
             edge
@@ -65,6 +104,7 @@ impl Graph {
     }
 
     pub fn remove_node(&mut self, key: NodeKey) {
+        self.revision += 1;
 
         let mut n = self.nodes.remove(key).unwrap();
         let n_keys = n.get_mut_keys();
@@ -73,18 +113,23 @@ impl Graph {
             let e = self.edges.remove(t_id).unwrap();
             let e_keys = e.get_keys();
 
-            self.nodes[e_keys.to].get_mut_keys().heads.remove(&e_keys.own);
+            if e_keys.to != key {
+                self.nodes[e_keys.to].get_mut_keys().heads.remove(&e_keys.own);
+            }
         }
 
         for h_id in n_keys.heads.drain() {
-            let e = self.edges.remove(h_id).unwrap();
-            let e_keys = e.get_keys();
+            // self-loops were already removed together with the tails
+            if let Some(e) = self.edges.remove(h_id) {
+                let e_keys = e.get_keys();
 
-            self.nodes[e_keys.from].get_mut_keys().tails.remove(&e_keys.own);
+                self.nodes[e_keys.from].get_mut_keys().tails.remove(&e_keys.own);
+            }
         }
     }
 
     pub fn remove_edge(&mut self, key: EdgeKey) {
+        self.revision += 1;
         let e = self.edges.remove(key).unwrap();
         let e_keys = e.get_keys();
 
@@ -94,7 +139,384 @@ impl Graph {
     }
 
 
+    pub fn find_edge(&self, tail_key: NodeKey, head_key: NodeKey) -> Option<EdgeKey> {
+        self.nodes[tail_key].get_keys().tails
+            .iter()
+            .copied()
+            .find(|&e_k| self.edges[e_k].get_keys().to == head_key)
+    }
+
+    // no-op when the opposite edge already exists
+    pub fn reverse_edge(&mut self, key: EdgeKey) -> bool {
+        let e_keys = self.edges[key].get_keys();
+        let (from, to) = (e_keys.from, e_keys.to);
+        if from == to || self.find_edge(to, from).is_some() { return false; }
+
+        self.revision += 1;
+        let e = &mut self.edges[key];
+        let (v1, v2) = (e.get_p1_v(), e.get_p2_v());
+        e.set_p1_v_uns(&v2);
+        e.set_p2_v_uns(&v1);
+        e.shape = e.shape.reversed();
+        let e_keys = e.get_mut_keys();
+        e_keys.from = to;
+        e_keys.to = from;
+
+        let from_keys = self.nodes[from].get_mut_keys();
+        from_keys.tails.remove(&key);
+        from_keys.heads.insert(key);
+        let to_keys = self.nodes[to].get_mut_keys();
+        to_keys.heads.remove(&key);
+        to_keys.tails.insert(key);
+        true
+    }
+
+    // adds the opposite edge, or removes it if the pair is already undirected
+    pub fn toggle_directed(&mut self, key: EdgeKey) {
+        let e_keys = self.edges[key].get_keys();
+        let (from, to) = (e_keys.from, e_keys.to);
+        if from == to { return; }
+
+        match self.find_edge(to, from) {
+            Some(r_k) => self.remove_edge(r_k),
+            None => {
+                let (v1, v2) = (self.nodes[from].get_p_v(), self.nodes[to].get_p_v());
+                self.add_edge(Edge::from(v2, v1), to, from);
+            }
+        }
+    }
+
+    pub fn edges_between(&self, keys: &HashSet<NodeKey>) -> Vec<EdgeKey> {
+        self.edges
+            .iter()
+            .filter(|(_k, e)| {
+                let e_keys = e.get_keys();
+                keys.contains(&e_keys.from) && keys.contains(&e_keys.to)
+            })
+            .map(|(k, _e)| k)
+            .collect::<Vec<EdgeKey>>()
+    }
+
+    pub fn project_on_edge(&self, key: EdgeKey, v: &Vec2) -> Vec2 {
+        let v = *v;
+        self.edges[key].path()
+            .windows(2)
+            .map(|w| {
+                let v12 = w[1] - w[0];
+                let t = match v12.length_squared() {
+                    l if l > 0.0 => ((v - w[0]).dot(v12) / l).clamp(0.0, 1.0),
+                    _ => 0.0
+                };
+                w[0] + v12 * t
+            })
+            .ord_subset_min_by_key(|p| p.distance_squared(v))
+            .unwrap_or(v)
+    }
+
+    // own value if it matches the schema type, otherwise the schema default
+    fn resolve_attr<'a>(&'a self, target: AttrTarget, own: &'a Attributes, name: &str) -> Option<&'a AttrValue> {
+        match own.get(name) {
+            Some(v) if self.schema.accepts(target, name, v) => Some(v),
+            _ => self.schema.defs(target).get(name)
+        }
+    }
+
+    pub fn node_attr(&self, key: NodeKey, name: &str) -> Option<&AttrValue> {
+        self.resolve_attr(AttrTarget::Node, &self.nodes[key].attributes, name)
+    }
+
+    pub fn edge_attr(&self, key: EdgeKey, name: &str) -> Option<&AttrValue> {
+        self.resolve_attr(AttrTarget::Edge, &self.edges[key].attributes, name)
+    }
+
+    pub fn set_node_attr(&mut self, key: NodeKey, name: &str, value: AttrValue) -> bool {
+        if !self.schema.accepts(AttrTarget::Node, name, &value) { return false; }
+        self.revision += 1;
+        self.nodes[key].attributes.insert(name.to_string(), value);
+        true
+    }
+
+    pub fn set_edge_attr(&mut self, key: EdgeKey, name: &str, value: AttrValue) -> bool {
+        if !self.schema.accepts(AttrTarget::Edge, name, &value) { return false; }
+        self.revision += 1;
+        self.edges[key].attributes.insert(name.to_string(), value);
+        true
+    }
+
+    pub fn remove_node_attr(&mut self, key: NodeKey, name: &str) -> Option<AttrValue> {
+        self.revision += 1;
+        self.nodes[key].attributes.remove(name)
+    }
+
+    pub fn remove_edge_attr(&mut self, key: EdgeKey, name: &str) -> Option<AttrValue> {
+        self.revision += 1;
+        self.edges[key].attributes.remove(name)
+    }
+
+    // values of another type already set on elements are dropped
+    pub fn define_attr(&mut self, target: AttrTarget, name: &str, default: AttrValue) {
+        self.revision += 1;
+        let t = default.get_type();
+        self.schema.defs_mut(target).insert(name.to_string(), default);
+
+        let name = name.to_string();
+        match target {
+            AttrTarget::Node => for n in self.nodes.values_mut() {
+                if n.attributes.get(&name).is_some_and(|v| v.get_type() != t) { n.attributes.remove(&name); }
+            }
+            AttrTarget::Edge => for e in self.edges.values_mut() {
+                if e.attributes.get(&name).is_some_and(|v| v.get_type() != t) { e.attributes.remove(&name); }
+            }
+        }
+    }
+
+    // removes the definition and the attribute from every element
+    pub fn undefine_attr(&mut self, target: AttrTarget, name: &str) {
+        self.revision += 1;
+        self.schema.defs_mut(target).remove(name);
+        match target {
+            AttrTarget::Node => for n in self.nodes.values_mut() { n.attributes.remove(name); }
+            AttrTarget::Edge => for e in self.edges.values_mut() { e.attributes.remove(name); }
+        }
+    }
+
+    // cost of an edge for the algorithms, the edge length unless a numeric attribute is named
+    pub fn edge_weight(&self, key: EdgeKey, attr: Option<&str>) -> f32 {
+        attr.and_then(|name| self.edge_attr(key, name))
+            .and_then(|v| v.as_number())
+            .unwrap_or_else(|| self.edges[key].get_length())
+    }
+
+    pub fn style_rules(&self) -> &[StyleRule] {
+        &self.style_rules
+    }
+
+    pub fn add_style_rule(&mut self, rule: StyleRule) {
+        self.style_changes += 1;
+        self.style_rules.push(rule);
+    }
+
+    pub fn set_style_rule(&mut self, i: usize, rule: StyleRule) {
+        self.style_changes += 1;
+        self.style_rules[i] = rule;
+    }
+
+    pub fn remove_style_rule(&mut self, i: usize) {
+        self.style_changes += 1;
+        self.style_rules.remove(i);
+    }
+
+    pub fn set_node_style(&mut self, key: NodeKey, style: NodeStyle) {
+        self.style_changes += 1;
+        self.nodes[key].style = style;
+    }
+
+    pub fn set_edge_style(&mut self, key: EdgeKey, style: EdgeStyle) {
+        self.style_changes += 1;
+        self.edges[key].style = style;
+    }
+
+    // matching rules are applied in order and the element's own style last
+    pub fn refresh_styles(&mut self) {
+        let revisions = (self.revision, self.style_changes);
+        if self.style_revision == Some(revisions) { return; }
+        self.style_revision = Some(revisions);
+
+        let node_styles = self.nodes
+            .iter()
+            .map(|(n_k, n)| {
+                let style = self.style_rules
+                    .iter()
+                    .filter(|rule| rule.target == AttrTarget::Node && rule.matches(self.node_attr(n_k, &rule.attr)))
+                    .fold(NodeStyle::default(), |acc, rule| acc.with(&rule.node));
+                (n_k, style.with(&n.style))
+            })
+            .collect::<Vec<(NodeKey, NodeStyle)>>();
+        for (n_k, style) in node_styles {
+            self.nodes[n_k].resolved = style;
+        }
+
+        let edge_styles = self.edges
+            .iter()
+            .map(|(e_k, e)| {
+                let style = self.style_rules
+                    .iter()
+                    .filter(|rule| rule.target == AttrTarget::Edge && rule.matches(self.edge_attr(e_k, &rule.attr)))
+                    .fold(EdgeStyle::default(), |acc, rule| acc.with(&rule.edge));
+                let keys = e.get_keys();
+                let end_r = [self.nodes[keys.from].get_radius(), self.nodes[keys.to].get_radius()];
+                (e_k, style.with(&e.style), end_r)
+            })
+            .collect::<Vec<(EdgeKey, EdgeStyle, [f32; 2])>>();
+        for (e_k, style, end_r) in edge_styles {
+            let e = &mut self.edges[e_k];
+            e.resolved = style;
+            e.end_r = Some(end_r);
+        }
+    }
+
+    pub fn set_edge_shape(&mut self, key: EdgeKey, shape: EdgeShape) {
+        self.revision += 1;
+        self.edges[key].set_shape(shape);
+    }
+
+    pub fn add_edge_bend(&mut self, key: EdgeKey, v: &Vec2) {
+        self.revision += 1;
+        self.edges[key].add_bend(*v);
+    }
+
+    pub fn move_edge_handle(&mut self, key: EdgeKey, i: usize, v: &Vec2) {
+        self.revision += 1;
+        self.edges[key].set_handle(i, *v);
+    }
+
+    pub fn remove_edge_handle(&mut self, key: EdgeKey, i: usize) {
+        self.revision += 1;
+        self.edges[key].remove_handle(i);
+    }
+
+    pub fn edge_handle_at(&self, mv: &Vec2) -> Option<(EdgeKey, usize)> {
+        let mv = *mv;
+        self.edges
+            .iter()
+            .flat_map(|(k, e)| e.handles().into_iter().enumerate().map(move |(i, h)| (k, i, h)))
+            .filter(|(_k, _i, h)| h.distance(mv) < HANDLE_RADIUS * 2.0)
+            .ord_subset_min_by_key(|(_k, _i, h)| h.distance(mv))
+            .map(|(k, i, _h)| (k, i))
+    }
+
+    // bends straight edges of reciprocal pairs to opposite sides so both stay visible
+    pub fn curve_reciprocal_edges(&mut self) {
+        let keys = self.edges
+            .iter()
+            .filter(|(_k, e)| *e.get_shape() == EdgeShape::Straight && !e.is_loop())
+            .filter(|(_k, e)| self.find_edge(e.get_keys().to, e.get_keys().from).is_some())
+            .map(|(k, _e)| k)
+            .collect::<Vec<EdgeKey>>();
+
+        for e_k in keys {
+            self.set_edge_shape(e_k, EdgeShape::Curved([0.5, CURVE_OFFSET / 2.0]));
+        }
+    }
+
+    // both halves keep the style overrides and their part of the shape,
+    // numeric attributes are weights and are shared in proportion to the half lengths
+    pub fn split_edge(&mut self, key: EdgeKey, v: &Vec2) -> NodeKey {
+        let e_keys = self.edges[key].get_keys();
+        let (from, to) = (e_keys.from, e_keys.to);
+
+        let reverse = match from != to {
+            true => self.find_edge(to, from),
+            false => None
+        };
+        let n_k = self.add_node(Node::from(*v));
+
+        self.split_into(key, n_k);
+        if let Some(r_k) = reverse {
+            self.split_into(r_k, n_k);
+        }
+        n_k
+    }
+
+    // replaces the edge with one into `n_k` and one out of it
+    fn split_into(&mut self, key: EdgeKey, n_k: NodeKey) {
+        let e_keys = self.edges[key].get_keys();
+        let (from, to) = (e_keys.from, e_keys.to);
+        let v = self.nodes[n_k].get_p_v();
+        let (shape1, shape2) = self.edges[key].split_shape(v);
+        let (_shape, attributes, style) = self.take_edge_data(key);
+        self.remove_edge(key);
+
+        let mut e1 = Edge::from(self.nodes[from].get_p_v(), v);
+        let mut e2 = Edge::from(v, self.nodes[to].get_p_v());
+        e1.set_shape(shape1.clone());
+        e2.set_shape(shape2.clone());
+        let (l1, l2) = (e1.get_length(), e2.get_length());
+        let k1 = match l1 + l2 {
+            l if l > 0.0 => l1 / l,
+            _ => 0.5
+        };
+        let share = |k: f32| attributes
+            .iter()
+            .map(|(name, value)| match value {
+                AttrValue::Number(x) => (name.clone(), AttrValue::Number(x * k)),
+                _ => (name.clone(), value.clone())
+            })
+            .collect::<Attributes>();
+
+        self.add_edge_with(e1, from, n_k, (shape1, share(k1), style.clone()));
+        self.add_edge_with(e2, n_k, to, (shape2, share(1.0 - k1), style));
+    }
+
+    // rewires all edges of `other` to `keep` and removes `other`,
+    // edges between the two nodes are dropped, rewired edges keep their data
+    // and `keep` takes the attributes it does not have yet
+    pub fn merge_nodes(&mut self, keep: NodeKey, other: NodeKey) {
+        if keep == other { return; }
+
+        let o_keys = self.nodes[other].get_keys();
+        let outgoing = o_keys.tails.iter().copied().collect::<Vec<EdgeKey>>();
+        let incoming = o_keys.heads.iter().copied().collect::<Vec<EdgeKey>>();
+
+        let heads = outgoing
+            .into_iter()
+            .map(|e_k| (self.edges[e_k].get_keys().to, self.take_edge_data(e_k)))
+            .collect::<Vec<_>>();
+        let tails = incoming
+            .into_iter()
+            .map(|e_k| (self.edges[e_k].get_keys().from, self.take_edge_data(e_k)))
+            .collect::<Vec<_>>();
+        let attributes = std::mem::take(&mut self.nodes[other].attributes);
+
+        self.remove_node(other);
+        let v = self.nodes[keep].get_p_v();
+        for (name, value) in attributes {
+            self.nodes[keep].attributes.entry(name).or_insert(value);
+        }
+
+        for (h_k, data) in heads.into_iter().filter(|(k, _d)| *k != keep && *k != other) {
+            let h_v = self.nodes[h_k].get_p_v();
+            self.add_edge_with(Edge::from(v, h_v), keep, h_k, data);
+        }
+        for (t_k, data) in tails.into_iter().filter(|(k, _d)| *k != keep && *k != other) {
+            let t_v = self.nodes[t_k].get_p_v();
+            self.add_edge_with(Edge::from(t_v, v), t_k, keep, data);
+        }
+    }
+
+    // shape, attributes and style overrides of an edge about to be removed
+    fn take_edge_data(&mut self, key: EdgeKey) -> (EdgeShape, Attributes, EdgeStyle) {
+        let e = &mut self.edges[key];
+        (std::mem::take(&mut e.shape), std::mem::take(&mut e.attributes), std::mem::take(&mut e.style))
+    }
+
+    // an edge already joining the nodes is kept as it is
+    fn add_edge_with(&mut self, edge: Edge, tail_key: NodeKey, head_key: NodeKey, data: (EdgeShape, Attributes, EdgeStyle)) -> EdgeKey {
+        if let Some(e_k) = self.find_edge(tail_key, head_key) { return e_k; }
+
+        let e_k = self.add_edge(edge, tail_key, head_key);
+        let e = &mut self.edges[e_k];
+        (e.shape, e.attributes, e.style) = data;
+        e_k
+    }
+
+    // merges the head into the tail, the result is placed in the middle of the edge
+    pub fn contract_edge(&mut self, key: EdgeKey) -> NodeKey {
+        let e_keys = self.edges[key].get_keys();
+        let (from, to) = (e_keys.from, e_keys.to);
+        if from == to {
+            self.remove_edge(key);
+            return from;
+        }
+        let v = (self.nodes[from].get_p_v() + self.nodes[to].get_p_v()) / 2.0;
+
+        self.merge_nodes(from, to);
+        self.set_node_pos(from, &v);
+        from
+    }
+
     pub fn set_node_pos(&mut self, key: NodeKey, v: &Vec2) {
+        self.revision += 1;
         let n = &mut self.nodes[key];
         n.set_p_v_uns(v);
 
@@ -107,6 +529,31 @@ impl Graph {
 
     }
 
+    pub fn degree(&self, key: NodeKey) -> usize {
+        self.neighbours(key).len()
+    }
+
+    pub fn neighbours(&self, key: NodeKey) -> HashSet<NodeKey> {
+        let keys = self.nodes[key].get_keys();
+
+        keys.tails
+            .iter()
+            .map(|&e_k| self.edges[e_k].get_keys().to)
+            .chain(keys.heads
+                .iter()
+                .map(|&e_k| self.edges[e_k].get_keys().from)
+            )
+            .filter(|&n_k| n_k != key)
+            .collect::<HashSet<NodeKey>>()
+    }
+
+    pub fn successors(&self, key: NodeKey) -> Vec<(NodeKey, EdgeKey)> {
+        self.nodes[key].get_keys().tails
+            .iter()
+            .map(|&e_k| (self.edges[e_k].get_keys().to, e_k))
+            .collect::<Vec<(NodeKey, EdgeKey)>>()
+    }
+
     pub fn find_nearest_node(&self, key: NodeKey) -> Option<NodeKey>{
         let v = self.nodes[key].get_p_v();
 
@@ -153,6 +600,20 @@ impl Graph {
 
     }
 
+    pub fn find_nodes_in_rect(&self, v1: Vec2, v2: Vec2) -> Vec<NodeKey> {
+        let min = v1.min(v2);
+        let max = v1.max(v2);
+
+        self.nodes
+            .iter()
+            .filter(|(_k, n)| {
+                let v = n.get_p_v();
+                min.x <= v.x && v.x <= max.x && min.y <= v.y && v.y <= max.y
+            })
+            .map(|(k, _n)| k)
+            .collect::<Vec<NodeKey>>()
+    }
+
 
 
 
@@ -163,7 +624,7 @@ impl Graph {
         self.nodes
             .iter()
             .filter(|(_k, n)|
-                Vec2::length_squared(n.get_p_v()-mv) < NODE_RADIUS.powi(2) * 4.0
+                Vec2::length_squared(n.get_p_v()-mv) < n.get_radius().max(NODE_RADIUS).powi(2) * 4.0
             )
             .ord_subset_min_by_key(|(_k, n)|
                 Vec2::length_squared(n.get_p_v()-mv)
@@ -177,26 +638,10 @@ impl Graph {
 
         self.edges
             .iter()
-            .filter(|(_k, e)| {
-                let v1 = e.get_p1_v();
-                let v2 = e.get_p2_v();
-                let v12 = v2 - v1;
-                let v10 = v0 - v1;
-                let proj_unnorm = v10.dot(v12);
-                let perp_proj = v10.perp_dot(v12.normalize());
-
-                0.0 <= proj_unnorm && proj_unnorm <= v12.length_squared() && perp_proj.abs() <= EDGE_THICKNESS
-            })
-            .ord_subset_min_by_key(|(_k, e)| {
-                let v1 = e.get_p1_v();
-                let v2 = e.get_p2_v();
-                let v12 = v2 - v1;
-                let v10 = v0 - v1;
-
-                let perp_proj_unnorm = v10.perp_dot(v12);
-                perp_proj_unnorm
-            })
-            .map(|(k, _e)| k)
+            .filter_map(|(k, e)| e.distance_to(v0).map(|d| (k, d)))
+            .filter(|(_k, d)| *d <= EDGE_THICKNESS)
+            .ord_subset_min_by_key(|(_k, d)| *d)
+            .map(|(k, _d)| k)
     }
 
 
@@ -210,10 +655,8 @@ impl Graph {
                 let sn = &self.nodes[sn_k];
                 let se = &self.edges[se_k];
                 let nv = sn.get_p_v();
-                let ev1 = se.get_p1_v();
-                let ev2 = se.get_p2_v();
                 let nd = nv.distance(mv);
-                let ed = (mv - ev1).perp_dot((ev2 - ev1).normalize());
+                let ed = se.distance_to(mv).unwrap_or(f32::INFINITY);
 
                 if nd < ed || nd < SELECTED_NODE_RADIUS { SL::Node(sn_k) }
                 else { SL::Edge(se_k) }
@@ -227,39 +670,58 @@ impl Graph {
 
 
 
-    pub fn draw_nodes(&self) {
-        for (_k, n) in &self.nodes {
-            n.draw(NODE_RADIUS, NODE_COLOR);
+    // elements outside of `filter` are drawn dimmed
+    pub fn draw_nodes(&self, filter: Option<&HashSet<NodeKey>>) {
+        for (k, n) in &self.nodes {
+            let alpha = match filter {
+                Some(keys) if !keys.contains(&k) => DIM_ALPHA,
+                _ => 1.0
+            };
+            n.draw_styled(alpha);
         }
     }
 
 
-    pub fn draw_edges(&self) {
+    pub fn draw_edges(&self, filter: Option<&HashSet<NodeKey>>) {
         for (_k, e) in &self.edges {
-            e.draw(EDGE_THICKNESS, EDGE_COLOR);
+            let keys = e.get_keys();
+            let alpha = match filter {
+                Some(nodes) if !(nodes.contains(&keys.from) && nodes.contains(&keys.to)) => DIM_ALPHA,
+                _ => 1.0
+            };
+            e.draw_styled(alpha);
         }
     }
 
-    pub fn draw_lenghts(&self) {
+    pub fn draw_edge_handles(&self) {
         for (_k, e) in &self.edges {
+            e.draw_handles(HANDLE_COLOR);
+        }
+    }
+
+    // world length in the configured units once the map is calibrated
+    pub fn format_length(&self, length: f32) -> String {
+        self.units.format(length, self.background.meters_per_unit)
+    }
+
+    pub fn draw_lenghts(&self) {
+        for (_k, e) in self.edges.iter().filter(|(_k, e)| !e.is_loop()) {
             let font_size = FONT_SIZE;
-            let str = format!("{:.0}", e.get_length());
+            let str = self.format_length(e.get_length());
             let str_len = str.len() as f32;
-            let v1 = e.get_p1_v();
-            let v2 = e.get_p2_v();
-            let vn = (v2 - v1).normalize();
+            let (mid, vn) = e.midpoint();
 
             let mut rotation = f32::atan2(vn.y, vn.x);
             let t_v;
             match -PI/2.0 < rotation && rotation < PI/2.0 {
                 true => {
-                    t_v = (v1 + v2)/2.0
+                    t_v = mid
                         - vn * font_size * str_len / 4.0
                         - vn.perp() * 5.0;
 
                 }
                 false => {
-                    t_v = (v1 + v2)/2.0
+                    t_v = mid
                         + vn * font_size * str_len / 4.0
                         + vn.perp() * 5.0;
                     rotation += PI;
@@ -310,4 +772,253 @@ impl Graph {
 
 
 
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use super::*;
 
+    #[test]
+    fn split_edge_and_its_reverse() {
+        // 0 <-> 1 and 1 -> 2
+        let (mut graph, n, e) = test_graph(&[(0.0, 0.0), (20.0, 0.0), (20.0, 20.0)], &[(0, 1)], true);
+        graph.add_edge(Edge::from(Vec2::new(20.0, 0.0), Vec2::new(20.0, 20.0)), n[1], n[2]);
+        let m = graph.split_edge(e[0], &Vec2::new(5.0, 0.0));
+
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.edges.len(), 5);
+        assert!(!graph.edges.contains_key(e[0]));
+        for (a, b) in [(n[0], m), (m, n[1]), (n[1], m), (m, n[0])] {
+            assert!(graph.find_edge(a, b).is_some());
+        }
+        assert_eq!(graph.edges[graph.find_edge(n[0], m).unwrap()].get_length(), 5.0);
+        assert_eq!(graph.edges[graph.find_edge(m, n[1]).unwrap()].get_length(), 15.0);
+
+        // a one-way edge stays one-way
+        let m = graph.split_edge(graph.find_edge(n[1], n[2]).unwrap(), &Vec2::new(20.0, 10.0));
+        assert!(graph.find_edge(m, n[1]).is_none() && graph.find_edge(n[2], m).is_none());
+    }
+
+    #[test]
+    fn merge_rewires_and_drops_inner_edges() {
+        // 0 -> 1 -> 2, 3 -> 1 and 0 -> 2
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0), (10.0, 10.0)], &[(0, 1), (1, 2), (3, 1), (0, 2)], false);
+        graph.merge_nodes(n[0], n[1]);
+
+        assert!(!graph.nodes.contains_key(n[1]));
+        // 0 -> 2 already existed and is not doubled, 0 -> 1 is gone with 1
+        assert_eq!(graph.edges.len(), 2);
+        assert!(graph.find_edge(n[0], n[2]).is_some());
+        assert!(graph.find_edge(n[3], n[0]).is_some());
+        assert!(graph.find_edge(n[0], n[0]).is_none());
+    }
+
+    #[test]
+    fn contract_to_the_middle() {
+        let (mut graph, n, e) = path_graph(3);
+        let k = graph.contract_edge(e[1]);
+
+        assert_eq!(k, n[1]);
+        assert_eq!(graph.nodes[k].get_p_v(), Vec2::new(15.0, 0.0));
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 2);
+        assert_eq!(graph.edges[graph.find_edge(n[0], k).unwrap()].get_p2_v(), Vec2::new(15.0, 0.0));
+    }
+
+    #[test]
+    fn reverse_one_way_edges_only() {
+        let (mut graph, n, e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)], &[(0, 1), (1, 2)], false);
+        assert!(graph.reverse_edge(e[0]));
+
+        let keys = graph.edges[e[0]].get_keys();
+        assert_eq!((keys.from, keys.to), (n[1], n[0]));
+        assert_eq!(graph.edges[e[0]].get_p1_v(), Vec2::new(10.0, 0.0));
+        assert_eq!(graph.find_edge(n[1], n[0]), Some(e[0]));
+        assert!(graph.find_edge(n[0], n[1]).is_none());
+
+        // the opposite edge exists
+        graph.toggle_directed(e[1]);
+        assert!(!graph.reverse_edge(e[1]));
+        assert_eq!(graph.edges.len(), 3);
+        graph.toggle_directed(e[1]);
+        assert!(graph.find_edge(n[2], n[1]).is_none());
+    }
+
+    #[test]
+    fn edges_inside_a_node_set() {
+        let (graph, n, e) = path_graph(3);
+        let keys = HashSet::from([n[0], n[1]]);
+        let inner = graph.edges_between(&keys);
+
+        assert_eq!(inner.len(), 2);
+        assert!(inner.contains(&e[0]));
+    }
+
+    #[test]
+    fn self_loops() {
+        let (mut graph, n, e) = test_graph(&[(0.0, 0.0), (10.0, 0.0)], &[(0, 0), (0, 1)], false);
+        assert!(graph.edges[e[0]].is_loop());
+        assert!(!graph.edges[e[1]].is_loop());
+        assert_eq!(graph.nodes[n[0]].get_keys().tails.len(), 2);
+
+        // a loop is its own opposite edge
+        assert!(!graph.reverse_edge(e[0]));
+        graph.toggle_directed(e[0]);
+        assert_eq!(graph.edges.len(), 2);
+
+        // nodes moved onto each other do not make a loop
+        graph.set_node_pos(n[1], &Vec2::ZERO);
+        assert!(!graph.edges[e[1]].is_loop());
+
+        assert_eq!(graph.contract_edge(e[0]), n[0]);
+        assert_eq!(graph.edges.len(), 1);
+        assert!(graph.find_edge(n[0], n[0]).is_none());
+    }
+
+    #[test]
+    fn removing_a_node_with_a_loop() {
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0)], &[(0, 0), (0, 1), (1, 0)], false);
+        graph.remove_node(n[0]);
+
+        assert!(graph.edges.is_empty());
+        assert!(graph.nodes[n[1]].get_keys().tails.is_empty() && graph.nodes[n[1]].get_keys().heads.is_empty());
+    }
+
+    // largest distance from the points to the drawn line of the edge
+    fn off_path(edge: &Edge, points: &[Vec2]) -> f32 {
+        points.iter().map(|&p| edge.distance_to(p).unwrap_or(f32::MAX)).fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn reversing_keeps_the_path() {
+        let (mut graph, _n, e) = test_graph(&[(0.0, 0.0), (40.0, 0.0), (40.0, 40.0)], &[(0, 1), (1, 2)], false);
+        graph.set_edge_shape(e[0], EdgeShape::Curved([0.3, 8.0]));
+        graph.set_edge_shape(e[1], EdgeShape::Polyline(vec![[0.25, 5.0], [0.75, -5.0]]));
+
+        for e_k in [e[0], e[1]] {
+            let before = graph.edges[e_k].path();
+            graph.reverse_edge(e_k);
+            let after = graph.edges[e_k].path();
+
+            assert_eq!(before.len(), after.len());
+            for (p, q) in before.iter().rev().zip(&after) {
+                assert!(p.distance(*q) < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn split_keeps_the_shape() {
+        let (mut graph, n, e) = test_graph(&[(0.0, 0.0), (40.0, 0.0), (0.0, 40.0), (40.0, 40.0)], &[(0, 1), (2, 3)], false);
+        graph.set_edge_shape(e[0], EdgeShape::Curved([0.5, 10.0]));
+        graph.set_edge_shape(e[1], EdgeShape::Polyline(vec![[0.25, 5.0], [0.75, -5.0]]));
+        let mut curve = Edge::from(Vec2::new(0.0, 0.0), Vec2::new(40.0, 0.0));
+        curve.set_shape(graph.edges[e[0]].get_shape().clone());
+        let mut polyline = Edge::from(Vec2::new(0.0, 40.0), Vec2::new(40.0, 40.0));
+        polyline.set_shape(graph.edges[e[1]].get_shape().clone());
+
+        let m = graph.split_edge(e[0], &curve.path()[8]);
+        for e_k in [graph.find_edge(n[0], m).unwrap(), graph.find_edge(m, n[1]).unwrap()] {
+            assert!(matches!(graph.edges[e_k].get_shape(), EdgeShape::Curved(_)));
+            assert!(off_path(&curve, &graph.edges[e_k].path()) < 0.1);
+        }
+
+        // the middle of the segment between the bends
+        let m = graph.split_edge(e[1], &Vec2::new(20.0, 40.0));
+        let halves = [graph.find_edge(n[2], m).unwrap(), graph.find_edge(m, n[3]).unwrap()];
+        for e_k in halves {
+            assert!(graph.edges[e_k].handles().len() == 1);
+            assert!(off_path(&polyline, &graph.edges[e_k].path()) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn merge_keeps_the_shape() {
+        let (mut graph, n, e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)], &[(1, 2)], false);
+        graph.set_edge_shape(e[0], EdgeShape::Curved([0.5, 4.0]));
+        graph.merge_nodes(n[0], n[1]);
+
+        let e_k = graph.find_edge(n[0], n[2]).unwrap();
+        assert!(*graph.edges[e_k].get_shape() == EdgeShape::Curved([0.5, 4.0]));
+    }
+
+    #[test]
+    fn split_shares_weights() {
+        let (mut graph, n, e) = test_graph(&[(0.0, 0.0), (40.0, 0.0)], &[(0, 1)], false);
+        graph.set_edge_attr(e[0], "cost", AttrValue::Number(8.0));
+        graph.set_edge_attr(e[0], "label", AttrValue::Text("a".to_string()));
+        let m = graph.split_edge(e[0], &Vec2::new(10.0, 0.0));
+
+        let (e1, e2) = (graph.find_edge(n[0], m).unwrap(), graph.find_edge(m, n[1]).unwrap());
+        assert_eq!(graph.edge_attr(e1, "cost"), Some(&AttrValue::Number(2.0)));
+        assert_eq!(graph.edge_attr(e2, "cost"), Some(&AttrValue::Number(6.0)));
+        assert_eq!(graph.edge_attr(e2, "label"), Some(&AttrValue::Text("a".to_string())));
+    }
+
+    #[test]
+    fn merge_keeps_attributes() {
+        let (mut graph, n, e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)], &[(1, 2)], false);
+        graph.set_node_attr(n[0], "label", AttrValue::Text("kept".to_string()));
+        graph.set_node_attr(n[1], "label", AttrValue::Text("merged".to_string()));
+        graph.set_node_attr(n[1], "size", AttrValue::Number(3.0));
+        graph.set_edge_attr(e[0], "cost", AttrValue::Number(4.0));
+        graph.merge_nodes(n[0], n[1]);
+
+        assert_eq!(graph.node_attr(n[0], "label"), Some(&AttrValue::Text("kept".to_string())));
+        assert_eq!(graph.node_attr(n[0], "size"), Some(&AttrValue::Number(3.0)));
+        let e_k = graph.find_edge(n[0], n[2]).unwrap();
+        assert_eq!(graph.edge_attr(e_k, "cost"), Some(&AttrValue::Number(4.0)));
+    }
+
+    #[test]
+    fn schema_defaults_and_types() {
+        let (mut graph, n, _e) = path_graph(2);
+        graph.set_node_attr(n[0], "size", AttrValue::Text("big".to_string()));
+        graph.define_attr(AttrTarget::Node, "size", AttrValue::Number(1.0));
+
+        // the value of another type is dropped and the default shows through
+        assert_eq!(graph.node_attr(n[0], "size"), Some(&AttrValue::Number(1.0)));
+        assert!(!graph.set_node_attr(n[0], "size", AttrValue::Bool(true)));
+        assert!(graph.set_node_attr(n[0], "size", AttrValue::Number(2.0)));
+        assert_eq!(graph.node_attr(n[0], "size"), Some(&AttrValue::Number(2.0)));
+
+        graph.undefine_attr(AttrTarget::Node, "size");
+        assert_eq!(graph.node_attr(n[0], "size"), None);
+    }
+
+    #[test]
+    fn styles_follow_rules_and_overrides() {
+        let (mut graph, n, e) = path_graph(2);
+        graph.set_node_attr(n[0], "size", AttrValue::Number(3.0));
+        let mut rule = StyleRule::new(AttrTarget::Node, "size");
+        rule.node.radius = Some(8.0);
+        rule.node.fill = Some([1.0, 0.0, 0.0, 1.0]);
+        graph.add_style_rule(rule);
+        graph.refresh_styles();
+
+        assert_eq!(graph.nodes[n[0]].resolved.radius, Some(8.0));
+        assert_eq!(graph.nodes[n[1]].resolved.radius, None);
+
+        // style changes do not count as graph changes but are still picked up
+        let revision = graph.revision();
+        graph.set_node_style(n[0], NodeStyle { radius: Some(5.0), ..NodeStyle::default() });
+        graph.set_edge_style(e[0], EdgeStyle { thickness: Some(2.0), ..EdgeStyle::default() });
+        assert_eq!(graph.revision(), revision);
+        graph.refresh_styles();
+
+        assert_eq!(graph.nodes[n[0]].resolved.radius, Some(5.0));
+        assert_eq!(graph.nodes[n[0]].resolved.fill, Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(graph.edges[e[0]].resolved.thickness, Some(2.0));
+    }
+
+    #[test]
+    fn split_keeps_the_style() {
+        let (mut graph, n, e) = test_graph(&[(0.0, 0.0), (40.0, 0.0)], &[(0, 1)], false);
+        let style = EdgeStyle { dash: Some([4.0, 2.0]), ..EdgeStyle::default() };
+        graph.set_edge_style(e[0], style.clone());
+        let m = graph.split_edge(e[0], &Vec2::new(10.0, 0.0));
+
+        for e_k in [graph.find_edge(n[0], m).unwrap(), graph.find_edge(m, n[1]).unwrap()] {
+            assert_eq!(*graph.edges[e_k].get_style(), style);
+        }
+    }
+}
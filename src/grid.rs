@@ -0,0 +1,57 @@
+use macroquad::camera::Camera2D;
+use macroquad::color::*;
+use macroquad::math::Vec2;
+use macroquad::shapes::draw_line;
+use macroquad::window::{screen_width, screen_height};
+
+
+
+pub const DRAW_GRID: bool = false;
+pub const SNAP_TO_GRID: bool = false;
+pub const GRID_SPACING: f32 = 20.0;
+pub const GRID_THICKNESS: f32 = 1.0;
+pub const GRID_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.12);
+const MAX_GRID_LINES: f32 = 400.0;
+
+
+pub fn snap_to_grid(v: Vec2, spacing: f32) -> Vec2 {
+    (v / spacing).round() * spacing
+}
+
+
+pub fn draw_grid(cam: &Camera2D, spacing: f32) {
+    let c1 = cam.screen_to_world(Vec2::ZERO);
+    let c2 = cam.screen_to_world(Vec2::new(screen_width(), screen_height()));
+    let min = c1.min(c2);
+    let max = c1.max(c2);
+
+    let lines = (max - min) / spacing;
+    if lines.x + lines.y > MAX_GRID_LINES { return; }
+
+    // one screen pixel wide regardless of zoom
+    let t = GRID_THICKNESS * 2.0 / (cam.zoom.x.abs() * screen_width());
+    let start = (min / spacing).floor() * spacing;
+    let mut x = start.x;
+    while x <= max.x {
+        draw_line(x, min.y, x, max.y, t, GRID_COLOR);
+        x += spacing;
+    }
+    let mut y = start.y;
+    while y <= max.y {
+        draw_line(min.x, y, max.x, y, t, GRID_COLOR);
+        y += spacing;
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snaps_to_the_nearest_crossing() {
+        assert_eq!(snap_to_grid(Vec2::new(29.0, -11.0), GRID_SPACING), Vec2::new(20.0, -20.0));
+        assert_eq!(snap_to_grid(Vec2::new(31.0, 9.0), GRID_SPACING), Vec2::new(40.0, 0.0));
+    }
+}
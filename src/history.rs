@@ -0,0 +1,102 @@
+use macroquad::math::Vec2;
+use crate::{Graph, NodeKey, NodeGetSet};
+
+
+
+pub const HISTORY_LENGTH: usize = 32;
+
+
+pub struct PosSnapshot {
+    positions: Vec<(NodeKey, Vec2)>
+}
+
+
+impl PosSnapshot {
+    pub fn new(graph: &Graph) -> Self {
+        Self {
+            positions: graph.nodes
+                .iter()
+                .map(|(k, n)| (k, n.get_p_v()))
+                .collect::<Vec<(NodeKey, Vec2)>>()
+        }
+    }
+
+    pub fn restore(&self, graph: &mut Graph) {
+        for (n_k, v) in &self.positions {
+            if graph.nodes.contains_key(*n_k) {
+                graph.set_node_pos(*n_k, v);
+            }
+        }
+    }
+}
+
+
+#[derive(Default)]
+pub struct PosHistory {
+    snapshots: Vec<PosSnapshot>
+}
+
+
+impl PosHistory {
+    pub fn new() -> Self {
+        Self {
+            snapshots: Vec::new()
+        }
+    }
+
+    pub fn push(&mut self, graph: &Graph) {
+        if self.snapshots.len() == HISTORY_LENGTH {
+            self.snapshots.remove(0);
+        }
+        self.snapshots.push(PosSnapshot::new(graph));
+    }
+
+    pub fn undo(&mut self, graph: &mut Graph) -> bool {
+        match self.snapshots.pop() {
+            Some(snapshot) => {
+                snapshot.restore(graph);
+                true
+            }
+            None => false
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use super::*;
+
+    #[test]
+    fn undo_restores_positions() {
+        let (mut graph, n, _e) = path_graph(2);
+        let mut history = PosHistory::new();
+        history.push(&graph);
+        graph.set_node_pos(n[1], &Vec2::new(50.0, 50.0));
+
+        assert!(history.undo(&mut graph));
+        assert_eq!(graph.nodes[n[1]].get_p_v(), Vec2::new(10.0, 0.0));
+        assert!(!history.undo(&mut graph));
+    }
+
+    #[test]
+    fn length_is_bounded() {
+        let (mut graph, n, _e) = path_graph(1);
+        let mut history = PosHistory::new();
+        for i in 0..HISTORY_LENGTH + 5 {
+            graph.set_node_pos(n[0], &Vec2::new(i as f32, 0.0));
+            history.push(&graph);
+        }
+
+        let mut undone = 0;
+        while history.undo(&mut graph) { undone += 1; }
+        assert_eq!(undone, HISTORY_LENGTH);
+        assert_eq!(graph.nodes[n[0]].get_p_v(), Vec2::new(5.0, 0.0));
+    }
+}
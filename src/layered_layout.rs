@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use macroquad::math::Vec2;
+use slotmap::SecondaryMap;
+use crate::{Graph, NodeKey, NodeGetSet, EdgeGetSet, HashSet};
+
+
+
+pub const LAYER_SPACING: f32 = 80.0;
+pub const LAYER_NODE_SPACING: f32 = 60.0;
+const ORDERING_SWEEPS: usize = 12;
+const BALANCING_PASSES: usize = 8;
+
+
+// Sugiyama layout: cycle removal, longest path layering, barycenter crossing
+// minimization and balanced coordinate assignment. Layers grow downwards
+// following edge directions, the result is centered on the old centroid.
+pub fn layered_layout(graph: &mut Graph, node_spacing: f32, layer_spacing: f32) {
+    let keys = graph.nodes.keys().collect::<Vec<NodeKey>>();
+    let n = keys.len();
+    if n == 0 { return; }
+
+    let index: SecondaryMap<NodeKey, usize> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, &k)| (k, i))
+        .collect();
+
+    let mut edges = HashSet::new();
+    for e in graph.edges.values() {
+        let e_keys = e.get_keys();
+        if e_keys.from != e_keys.to {
+            edges.insert((index[e_keys.from], index[e_keys.to]));
+        }
+    }
+    let mut edges = edges.into_iter().collect::<Vec<(usize, usize)>>();
+    edges.sort();
+
+    let dag = remove_cycles(n, &edges);
+    let layer = assign_layers(n, &dag);
+    let (mut layers, up, down) = insert_dummies(n, &dag, &layer);
+
+    minimize_crossings(&mut layers, &up, &down);
+    let x = assign_coordinates(&layers, &up, &down, node_spacing);
+
+    let old_centroid = keys
+        .iter()
+        .fold(Vec2::ZERO, |acc, &k| acc + graph.nodes[k].get_p_v()) / n as f32;
+    let new_pos = (0..n)
+        .map(|i| Vec2::new(x[i], layer[i] as f32 * layer_spacing))
+        .collect::<Vec<Vec2>>();
+    let new_centroid = new_pos.iter().fold(Vec2::ZERO, |acc, &v| acc + v) / n as f32;
+
+    for (i, &n_k) in keys.iter().enumerate() {
+        let v = new_pos[i] - new_centroid + old_centroid;
+        graph.set_node_pos(n_k, &v);
+    }
+}
+
+
+
+fn remove_cycles(n: usize, edges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut out = vec![Vec::new(); n];
+    for &(a, b) in edges {
+        out[a].push(b);
+    }
+
+    // 0 - unvisited, 1 - on dfs stack, 2 - finished
+    let mut state = vec![0u8; n];
+    let mut back = HashSet::new();
+
+    for root in 0..n {
+        if state[root] != 0 { continue; }
+
+        let mut stack = vec![(root, 0)];
+        state[root] = 1;
+        while let Some(&mut (v, ref mut i)) = stack.last_mut() {
+            if *i < out[v].len() {
+                let w = out[v][*i];
+                *i += 1;
+                match state[w] {
+                    0 => {
+                        state[w] = 1;
+                        stack.push((w, 0));
+                    }
+                    1 => { back.insert((v, w)); }
+                    _ => {}
+                }
+            } else {
+                state[v] = 2;
+                stack.pop();
+            }
+        }
+    }
+
+    let mut dag = edges
+        .iter()
+        .map(|&(a, b)| if back.contains(&(a, b)) { (b, a) } else { (a, b) })
+        .collect::<Vec<(usize, usize)>>();
+    dag.sort();
+    dag.dedup();
+    dag
+}
+
+
+fn assign_layers(n: usize, dag: &[(usize, usize)]) -> Vec<usize> {
+    let mut out = vec![Vec::new(); n];
+    let mut in_deg = vec![0; n];
+    for &(a, b) in dag {
+        out[a].push(b);
+        in_deg[b] += 1;
+    }
+
+    let mut layer = vec![0; n];
+    let mut queue = (0..n).filter(|&v| in_deg[v] == 0).collect::<VecDeque<usize>>();
+    while let Some(v) = queue.pop_front() {
+        for &w in &out[v] {
+            layer[w] = layer[w].max(layer[v] + 1);
+            in_deg[w] -= 1;
+            if in_deg[w] == 0 { queue.push_back(w); }
+        }
+    }
+    layer
+}
+
+
+type Adjacency = Vec<Vec<usize>>;
+
+fn insert_dummies(n: usize, dag: &[(usize, usize)], layer: &[usize]) -> (Adjacency, Adjacency, Adjacency) {
+    let layer_count = layer.iter().max().map_or(0, |&l| l + 1);
+    let mut layers = vec![Vec::new(); layer_count];
+    for (v, &l) in layer.iter().enumerate() {
+        layers[l].push(v);
+    }
+
+    let mut up = vec![Vec::new(); n];
+    let mut down = vec![Vec::new(); n];
+    for &(a, b) in dag {
+        let mut prev = a;
+        for layer_nodes in &mut layers[layer[a] + 1..layer[b]] {
+            let dummy = up.len();
+            up.push(vec![prev]);
+            down.push(Vec::new());
+            down[prev].push(dummy);
+            layer_nodes.push(dummy);
+            prev = dummy;
+        }
+        down[prev].push(b);
+        up[b].push(prev);
+    }
+    (layers, up, down)
+}
+
+
+fn positions(layers: &[Vec<usize>], size: usize) -> Vec<f32> {
+    let mut pos = vec![0.0; size];
+    for layer in layers {
+        for (i, &v) in layer.iter().enumerate() {
+            pos[v] = i as f32;
+        }
+    }
+    pos
+}
+
+
+fn crossings(layers: &[Vec<usize>], down: &[Vec<usize>]) -> usize {
+    let pos = positions(layers, down.len());
+    let mut count = 0;
+
+    for layer in layers {
+        let segments = layer
+            .iter()
+            .flat_map(|&v| down[v].iter().map(move |&w| (v, w)))
+            .collect::<Vec<(usize, usize)>>();
+
+        for (i, &(a1, b1)) in segments.iter().enumerate() {
+            for &(a2, b2) in &segments[i + 1..] {
+                if (pos[a1] - pos[a2]) * (pos[b1] - pos[b2]) < 0.0 { count += 1; }
+            }
+        }
+    }
+    count
+}
+
+
+fn minimize_crossings(layers: &mut [Vec<usize>], up: &[Vec<usize>], down: &[Vec<usize>]) {
+    let mut best = layers.to_vec();
+    let mut best_crossings = crossings(layers, down);
+
+    for sweep in 0..ORDERING_SWEEPS {
+        let downwards = sweep % 2 == 0;
+        let order = match downwards {
+            true => (1..layers.len()).collect::<Vec<usize>>(),
+            false => (0..layers.len().saturating_sub(1)).rev().collect::<Vec<usize>>()
+        };
+
+        for l in order {
+            let pos = positions(layers, up.len());
+            let adjacent = if downwards { up } else { down };
+
+            let mut keyed = layers[l]
+                .iter()
+                .map(|&v| {
+                    let nbs = &adjacent[v];
+                    let bary = match nbs.is_empty() {
+                        true => pos[v],
+                        false => nbs.iter().map(|&w| pos[w]).sum::<f32>() / nbs.len() as f32
+                    };
+                    (bary, v)
+                })
+                .collect::<Vec<(f32, usize)>>();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[l] = keyed.into_iter().map(|(_b, v)| v).collect();
+        }
+
+        let c = crossings(layers, down);
+        if c < best_crossings {
+            best_crossings = c;
+            best = layers.to_vec();
+        }
+    }
+    layers.clone_from_slice(&best);
+}
+
+
+fn assign_coordinates(layers: &[Vec<usize>], up: &[Vec<usize>], down: &[Vec<usize>], spacing: f32) -> Vec<f32> {
+    let mut x = vec![0.0; up.len()];
+    for layer in layers {
+        let width = (layer.len() as f32 - 1.0) * spacing;
+        for (i, &v) in layer.iter().enumerate() {
+            x[v] = i as f32 * spacing - width / 2.0;
+        }
+    }
+
+    for _ in 0..BALANCING_PASSES {
+        for layer in layers {
+            let desired = layer
+                .iter()
+                .map(|&v| {
+                    let nbs = up[v].iter().chain(down[v].iter());
+                    let count = up[v].len() + down[v].len();
+                    match count {
+                        0 => x[v],
+                        _ => nbs.map(|&w| x[w]).sum::<f32>() / count as f32
+                    }
+                })
+                .collect::<Vec<f32>>();
+
+            let mut placed = Vec::with_capacity(layer.len());
+            for (i, &d) in desired.iter().enumerate() {
+                let v = match i {
+                    0 => d,
+                    _ => d.max(placed[i - 1] + spacing)
+                };
+                placed.push(v);
+            }
+
+            let shift = (desired.iter().sum::<f32>() - placed.iter().sum::<f32>()) / layer.len() as f32;
+            for (i, &v) in layer.iter().enumerate() {
+                x[v] = placed[i] + shift;
+            }
+        }
+    }
+    x
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use super::*;
+
+    #[test]
+    fn longest_path_layers() {
+        // 0 -> 1 -> 2 and the shortcut 0 -> 2
+        let dag = [(0, 1), (0, 2), (1, 2)];
+        assert_eq!(assign_layers(3, &dag), vec![0, 1, 2]);
+
+        // sources are on the first layer
+        let dag = [(0, 2), (1, 2), (2, 3)];
+        assert_eq!(assign_layers(4, &dag), vec![0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn cycles_are_broken() {
+        let edges = [(0, 1), (1, 2), (2, 0)];
+        let dag = remove_cycles(3, &edges);
+        let mut layer = assign_layers(3, &dag);
+
+        assert_eq!(dag.len(), 3);
+        layer.sort();
+        assert_eq!(layer, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn dummies_span_long_edges() {
+        let dag = [(0, 1), (1, 2), (0, 2)];
+        let layer = assign_layers(3, &dag);
+        let (layers, up, down) = insert_dummies(3, &dag, &layer);
+
+        // one dummy on the middle layer for 0 -> 2
+        assert_eq!(up.len(), 4);
+        assert_eq!(layers[1].len(), 2);
+        assert_eq!(down[0].len(), 2);
+        assert_eq!(up[2].len(), 2);
+    }
+
+    #[test]
+    fn layers_follow_edge_directions() {
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (50.0, -30.0), (-20.0, 40.0)], &[(0, 1), (1, 2)], false);
+
+        layered_layout(&mut graph, LAYER_NODE_SPACING, LAYER_SPACING);
+        let y = n.iter().map(|&k| graph.nodes[k].get_p_v().y).collect::<Vec<f32>>();
+
+        assert!((y[1] - y[0] - LAYER_SPACING).abs() < 1e-3);
+        assert!((y[2] - y[1] - LAYER_SPACING).abs() < 1e-3);
+    }
+}
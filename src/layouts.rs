@@ -0,0 +1,218 @@
+use std::f32::consts::PI;
+use std::collections::VecDeque;
+use macroquad::math::Vec2;
+use slotmap::SecondaryMap;
+use crate::{Graph, NodeKey, NodeGetSet, HashSet};
+
+
+
+pub const LAYOUT_SPACING: f32 = 60.0;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum CircleOrder {Component, Name}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum TreeStyle {Tidy, Radial}
+
+
+
+// weakly connected components of the given nodes, each in bfs order
+pub fn components(graph: &Graph, keys: &[NodeKey]) -> Vec<Vec<NodeKey>> {
+    let allowed = keys.iter().copied().collect::<HashSet<NodeKey>>();
+    let mut visited = HashSet::new();
+    let mut comps = Vec::new();
+
+    for &root in keys {
+        if !visited.insert(root) { continue; }
+
+        let mut comp = Vec::new();
+        let mut queue = VecDeque::from([root]);
+        while let Some(n_k) = queue.pop_front() {
+            comp.push(n_k);
+            let mut nbs = graph.neighbours(n_k).into_iter().collect::<Vec<NodeKey>>();
+            nbs.sort();
+            for nb_k in nbs {
+                if allowed.contains(&nb_k) && visited.insert(nb_k) {
+                    queue.push_back(nb_k);
+                }
+            }
+        }
+        comps.push(comp);
+    }
+    comps
+}
+
+
+pub fn order_by_component(graph: &Graph, keys: &[NodeKey]) -> Vec<NodeKey> {
+    let mut comps = components(graph, keys);
+    comps.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    comps.concat()
+}
+
+
+pub fn order_by_name(keys: &[NodeKey], names: &SecondaryMap<NodeKey, Box<String>>) -> Vec<NodeKey> {
+    let mut keys = keys.to_vec();
+    keys.sort_by(|&a, &b| {
+        match (names.get(a), names.get(b)) {
+            (Some(na), Some(nb)) => na.cmp(nb),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.cmp(&b)
+        }
+    });
+    keys
+}
+
+
+fn centroid(graph: &Graph, keys: &[NodeKey]) -> Vec2 {
+    keys.iter().fold(Vec2::ZERO, |acc, &k| acc + graph.nodes[k].get_p_v()) / keys.len() as f32
+}
+
+
+pub fn circular_layout(graph: &mut Graph, keys: &[NodeKey], spacing: f32) {
+    if keys.is_empty() { return; }
+
+    let c = centroid(graph, keys);
+    let n = keys.len() as f32;
+    let r = (spacing * n / (2.0 * PI)).max(spacing);
+
+    for (i, &n_k) in keys.iter().enumerate() {
+        let angle = 2.0 * PI * i as f32 / n - PI / 2.0;
+        let v = c + Vec2::new(angle.cos(), angle.sin()) * r;
+        graph.set_node_pos(n_k, &v);
+    }
+}
+
+
+pub fn grid_layout(graph: &mut Graph, keys: &[NodeKey], spacing: f32) {
+    if keys.is_empty() { return; }
+
+    let c = centroid(graph, keys);
+    let cols = (keys.len() as f32).sqrt().ceil() as usize;
+    let rows = keys.len().div_ceil(cols);
+    let size = Vec2::new((cols - 1) as f32, (rows - 1) as f32) * spacing;
+
+    for (i, &n_k) in keys.iter().enumerate() {
+        let v = c - size / 2.0 + Vec2::new((i % cols) as f32, (i / cols) as f32) * spacing;
+        graph.set_node_pos(n_k, &v);
+    }
+}
+
+
+// bfs spanning tree over the given nodes, returns children lists and depths
+fn spanning_tree(graph: &Graph, root: NodeKey, keys: &[NodeKey]) -> (SecondaryMap<NodeKey, Vec<NodeKey>>, SecondaryMap<NodeKey, usize>) {
+    let allowed = keys.iter().copied().collect::<HashSet<NodeKey>>();
+    let mut children: SecondaryMap<NodeKey, Vec<NodeKey>> = SecondaryMap::new();
+    let mut depth: SecondaryMap<NodeKey, usize> = SecondaryMap::new();
+
+    depth.insert(root, 0);
+    children.insert(root, Vec::new());
+    let mut queue = VecDeque::from([root]);
+    while let Some(n_k) = queue.pop_front() {
+        let mut nbs = graph.neighbours(n_k).into_iter().collect::<Vec<NodeKey>>();
+        nbs.sort();
+        for nb_k in nbs {
+            if allowed.contains(&nb_k) && !depth.contains_key(nb_k) {
+                depth.insert(nb_k, depth[n_k] + 1);
+                children.insert(nb_k, Vec::new());
+                children[n_k].push(nb_k);
+                queue.push_back(nb_k);
+            }
+        }
+    }
+    (children, depth)
+}
+
+
+// leaves get consecutive slots, parents are centered over their children
+fn assign_slots(children: &SecondaryMap<NodeKey, Vec<NodeKey>>, n_k: NodeKey, next: &mut f32, slots: &mut SecondaryMap<NodeKey, f32>) {
+    let ch = &children[n_k];
+    match ch.is_empty() {
+        true => {
+            slots.insert(n_k, *next);
+            *next += 1.0;
+        }
+        false => {
+            for &c_k in ch {
+                assign_slots(children, c_k, next, slots);
+            }
+            let slot = (slots[ch[0]] + slots[ch[ch.len() - 1]]) / 2.0;
+            slots.insert(n_k, slot);
+        }
+    }
+}
+
+
+// nodes of `keys` not reachable from the root keep their positions
+pub fn tree_layout(graph: &mut Graph, root: NodeKey, keys: &[NodeKey], spacing: f32, style: TreeStyle) {
+    let mut keys = keys.to_vec();
+    if !keys.contains(&root) { keys.push(root); }
+
+    let (children, depth) = spanning_tree(graph, root, &keys);
+    let mut slots = SecondaryMap::new();
+    let mut leaves = 0.0;
+    assign_slots(&children, root, &mut leaves, &mut slots);
+
+    let root_v = graph.nodes[root].get_p_v();
+    let root_slot = slots[root];
+
+    for (n_k, &slot) in &slots {
+        let d = depth[n_k] as f32;
+        let v = match style {
+            TreeStyle::Tidy => root_v + Vec2::new((slot - root_slot) * spacing, d * spacing),
+            TreeStyle::Radial => {
+                let angle = 2.0 * PI * slot / leaves;
+                root_v + Vec2::new(angle.cos(), angle.sin()) * d * spacing
+            }
+        };
+        graph.set_node_pos(n_k, &v);
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use super::*;
+
+    #[test]
+    fn components_of_the_given_nodes() {
+        // 0 - 1 - 2 and 3 - 4, without 1 the first splits
+        let (graph, n, _e) = test_graph(&[(0.0, 0.0); 5], &[(0, 1), (1, 2), (3, 4)], false);
+        assert_eq!(components(&graph, &n).len(), 2);
+        assert_eq!(components(&graph, &[n[0], n[2], n[3], n[4]]).len(), 3);
+        assert_eq!(order_by_component(&graph, &[n[3], n[4], n[0], n[1], n[2]])[..3], n[..3]);
+    }
+
+    #[test]
+    fn circle_and_grid() {
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)], &[], false);
+        circular_layout(&mut graph, &n, LAYOUT_SPACING);
+        let c = Vec2::new(5.0, 5.0);
+        let r = graph.nodes[n[0]].get_p_v().distance(c);
+        assert!(n.iter().all(|&k| (graph.nodes[k].get_p_v().distance(c) - r).abs() < 1e-3));
+        assert!((r - LAYOUT_SPACING).abs() < 1e-3);
+
+        grid_layout(&mut graph, &n, LAYOUT_SPACING);
+        let v = n.iter().map(|&k| graph.nodes[k].get_p_v() - c).collect::<Vec<Vec2>>();
+        let h = LAYOUT_SPACING / 2.0;
+        let expected = [Vec2::new(-h, -h), Vec2::new(h, -h), Vec2::new(-h, h), Vec2::new(h, h)];
+        assert!(v.iter().zip(expected).all(|(a, b)| a.distance(b) < 1e-3));
+    }
+
+    #[test]
+    fn tidy_tree() {
+        // 0 has the children 1 and 2, 1 has the child 3
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0); 4], &[(0, 1), (0, 2), (1, 3)], false);
+        tree_layout(&mut graph, n[0], &n, 10.0, TreeStyle::Tidy);
+        let v = n.iter().map(|&k| graph.nodes[k].get_p_v()).collect::<Vec<Vec2>>();
+
+        // the root stays in place centered over its children
+        assert_eq!(v[0], Vec2::ZERO);
+        assert_eq!(v[1], Vec2::new(-5.0, 10.0));
+        assert_eq!(v[2], Vec2::new(5.0, 10.0));
+        assert_eq!(v[3], Vec2::new(-5.0, 20.0));
+    }
+}
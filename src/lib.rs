@@ -17,7 +17,29 @@ pub use node_src::node_pos::*;
 pub use node_src::node_keys::*;
 
 mod path_algo; pub use path_algo::*;
+#[cfg(test)] mod testing;
 mod graph; pub use graph::*;
+mod stats; pub use stats::*;
+mod force_layout; pub use force_layout::*;
+mod layered_layout; pub use layered_layout::*;
+mod history; pub use history::*;
+mod layouts; pub use layouts::*;
+mod selection; pub use selection::*;
+mod grid; pub use grid::*;
+mod align; pub use align::*;
+mod generators; pub use generators::*;
+mod geometric; pub use geometric::*;
+mod structures; pub use structures::*;
+mod scatter; pub use scatter::*;
+mod attributes; pub use attributes::*;
+mod style; pub use style::*;
+mod search; pub use search::*;
+mod view; pub use view::*;
+mod minimap; pub use minimap::*;
+mod background; pub use background::*;
+mod units; pub use units::*;
+mod visualize; pub use visualize::*;
+mod traversal; pub use traversal::*;
 mod variables; pub use variables::*;
 
 
@@ -5,17 +5,18 @@ use egui_macroquad::egui::epaint::Shadow;
 
 use macroquad::camera::{
     Camera2D,
-    set_camera
+    set_camera,
+    set_default_camera
 };
 
 use macroquad::math::{Vec2, vec2};
 use macroquad::prelude::load_texture;
-use macroquad::rand::ChooseRandom;
-use macroquad::texture::{draw_texture, Texture2D};
+use macroquad::texture::Texture2D;
+use macroquad::time::get_frame_time;
 
-use rand::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use macroquad_project::*;
-use serde_json;
 use slotmap::{Key, SecondaryMap};
 
 
@@ -29,6 +30,74 @@ fn window_conf() -> Conf {
 }
 
 
+fn node_name<S: std::fmt::Display>(names: &SecondaryMap<NodeKey, S>, n_k: NodeKey) -> String {
+    match names.get(n_k) {
+        Some(name) => format!("{} ({:?})", name, n_k),
+        None => format!("{:?}", n_k)
+    }
+}
+
+
+fn attr_value_ui(ui: &mut egui::Ui, value: &mut AttrValue) -> bool {
+    match value {
+        AttrValue::Text(s) => ui.text_edit_singleline(s).changed(),
+        AttrValue::Number(x) => ui.add(egui::DragValue::new(x).speed(0.1)).changed(),
+        AttrValue::Bool(b) => ui.checkbox(b, "").changed(),
+        AttrValue::Color(c) => ui.color_edit_button_rgba_unmultiplied(c).changed()
+    }
+}
+
+
+fn optional_ui<T>(ui: &mut egui::Ui, label: &str, value: &mut Option<T>, default: impl FnOnce() -> T,
+                  edit: impl FnOnce(&mut egui::Ui, &mut T) -> bool) -> bool {
+    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+        let mut on = value.is_some();
+        let mut changed = ui.checkbox(&mut on, label).changed();
+        match on {
+            true => changed |= edit(ui, value.get_or_insert_with(default)),
+            false => *value = None
+        }
+        changed
+    }).inner
+}
+
+
+fn color_ui(ui: &mut egui::Ui, c: &mut [f32; 4]) -> bool {
+    ui.color_edit_button_rgba_unmultiplied(c).changed()
+}
+
+
+fn node_style_ui(ui: &mut egui::Ui, style: &mut NodeStyle) -> bool {
+    let mut changed = optional_ui(ui, "fill", &mut style.fill, || NODE_COLOR.into(), color_ui);
+    changed |= optional_ui(ui, "outline", &mut style.outline, || BLACK.into(), color_ui);
+    changed |= optional_ui(ui, "outline thickness", &mut style.outline_thickness, || NODE_OUTLINE_THICKNESS,
+                           |ui, t| ui.add(egui::DragValue::new(t).clamp_range(0.5..=20.0).speed(0.05)).changed());
+    changed |= optional_ui(ui, "radius", &mut style.radius, || NODE_RADIUS,
+                           |ui, r| ui.add(egui::DragValue::new(r).clamp_range(1.0..=100.0).speed(0.1)).changed());
+    changed |= optional_ui(ui, "shape", &mut style.shape, || NodeShape::Circle, |ui, shape| {
+        let mut changed = false;
+        for s in NodeShape::ALL {
+            changed |= ui.radio_value(shape, s, s.name()).changed();
+        }
+        changed
+    });
+    changed
+}
+
+
+fn edge_style_ui(ui: &mut egui::Ui, style: &mut EdgeStyle) -> bool {
+    let mut changed = optional_ui(ui, "color", &mut style.color, || EDGE_COLOR.into(), color_ui);
+    changed |= optional_ui(ui, "thickness", &mut style.thickness, || EDGE_THICKNESS,
+                           |ui, t| ui.add(egui::DragValue::new(t).clamp_range(0.5..=20.0).speed(0.05)).changed());
+    changed |= optional_ui(ui, "dash", &mut style.dash, || [DASH_LENGTH, DASH_GAP], |ui, d| {
+        let a = ui.add(egui::DragValue::new(&mut d[0]).clamp_range(1.0..=100.0).speed(0.1)).changed();
+        let b = ui.add(egui::DragValue::new(&mut d[1]).clamp_range(1.0..=100.0).speed(0.1)).changed();
+        a || b
+    });
+    changed
+}
+
+
 #[macroquad::main(window_conf)]
 async fn main() {
     prevent_quit();
@@ -49,7 +118,26 @@ async fn main() {
     };
 
     let mut held_sn_k_v: Option<(NodeKey, Vec2)> = None;
+    let mut held_left_node = false;
     let mut shift_held_sn_k: Option<NodeKey> = None;
+    let mut held_handle: Option<(EdgeKey, usize)> = None;
+    let mut inspected = SL::None;
+    let mut new_attr_key = String::new();
+    let mut new_attr_type = AttrType::Text;
+    let mut new_def_name = String::new();
+    let mut new_def_type = AttrType::Number;
+    let mut new_def_target = AttrTarget::Edge;
+    let mut weight_attr: Option<String> = None;
+    let mut search_query = String::new();
+    let mut search_filter = false;
+    let mut minimap = Minimap::new();
+    let mut algo_player = AlgoPlayer::new();
+    let mut show_traversal = false;
+    let mut traversal_kind = TraversalKind::Bfs;
+    let mut traversal_directed = false;
+    let mut calibrating = false;
+    let mut calibration_points: Vec<Vec2> = Vec::new();
+    let mut calibration_distance = CALIBRATION_DISTANCE;
     let mut start_n_k = None;
     let mut finish_n_k = None;
     let mut map: Option<Texture2D> = None;
@@ -63,22 +151,84 @@ async fn main() {
     let mut max_edge_length = MAX_EDGE_LENGTH;
     let mut map_png_path = MAP_PNG_PATH.to_string();
     let mut load_map = None;
-
-    let mut cam: Camera2D = Camera2D::default();
-    cam.zoom = 2.0/vec2(WIDTH, -HEIGHT);
+    if let Some(path) = &graph.background.path {
+        map_png_path = path.clone();
+        load_map = Some(path.clone());
+    }
+    let mut stats: Option<GraphStats> = None;
+    let mut force_layout = ForceLayout::new(IDEAL_EDGE_LENGTH);
+    let mut step_layout = false;
+    let mut pos_history = PosHistory::new();
+    let mut selection = Selection::new();
+    let mut rect_sel_v: Option<Vec2> = None;
+    let mut layout_selection_only = true;
+    let mut layout_spacing = LAYOUT_SPACING;
+    let mut circle_order = CircleOrder::Component;
+    let mut tree_style = TreeStyle::Tidy;
+    let mut show_grid = DRAW_GRID;
+    let mut snap_nodes = SNAP_TO_GRID;
+    let mut grid_spacing = GRID_SPACING;
+    let mut gen_model = GraphModel::ErdosRenyi;
+    let mut gen_n = GEN_NODES;
+    let mut gen_p = GEN_P;
+    let mut gen_m = GEN_M;
+    let mut gen_k = GEN_K;
+    let mut gen_beta = GEN_BETA;
+    let mut gen_seed = GEN_SEED;
+    let mut structure = Structure::SquareLattice;
+    let mut structure_size = STRUCTURE_SIZE;
+    let mut structure_spacing = STRUCTURE_SPACING;
+    let mut proximity = Proximity::Delaunay;
+    let mut nearest_k = NEAREST_NEIGHBOURS;
+    let mut connect_selection_only = false;
+    let mut connect_length_bounds = true;
+    let mut region = Region::new();
+    let mut region_shape = RegionShape::Rectangle;
+    let mut region_rect_v: Option<Vec2> = None;
+    let mut drawing_region = false;
+    // the RMB press that closed a polygon must not delete anything until released
+    let mut rmb_consumed = false;
+    let mut seed = GEN_SEED;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut cam = Camera2D {
+        zoom: 2.0/vec2(WIDTH, -HEIGHT),
+        ..Camera2D::default()
+    };
 
     let help = "Ctrl + LMB   - movement
 LMB          - create node, hold and drag
                to create edge with node
-               or join existing nodes
-Shift + LMB  - move node
+               or join existing nodes,
+               drag out and back for loop
+Shift + LMB  - move node or edge handle
+Shift + RMB  - remove edge handle
+Alt + LMB    - select node, drag to select
+               area, click empty to clear
 RMB          - hold to delete node or edge
-mouse wheel  - zoom
+mouse wheel  - zoom at cursor
++ / -        - zoom in / out at cursor
+Home         - fit whole graph
+End          - fit selection
 S            - set start for path finding
 F            - set finish for path finding
 N            - generate nodes
 E            - generate edges
-T            - name node";
+T            - name node
+G            - place structure at cursor
+I            - insert node into edge
+C            - contract edge
+M            - merge selected nodes
+R            - reverse edge direction
+Shift + R    - reverse edges between
+               selected nodes
+D            - toggle edge directedness
+V            - toggle curved edge
+B            - add bend point to edge
+P            - pin node for auto-layout
+L            - toggle traversal tree from
+               the clicked node
+Ctrl + Z     - undo layout";
 
 
     let mut names: SecondaryMap<NodeKey, Box<String>> = SecondaryMap::new();
@@ -88,7 +238,22 @@ T            - name node";
     while !is_key_pressed(KeyCode::Escape) && !is_quit_requested() {
         let mut mouse_over_ui = false;
         let m_v = cam.screen_to_world(Vec2::from(mouse_position()));
+        let gm_v = if snap_nodes { snap_to_grid(m_v, grid_spacing) } else { m_v };
         let mut selected = graph.selected_k_v(&m_v);
+        let mut new_graph: Option<Graph> = None;
+        let path = match (start_n_k, finish_n_k) {
+            (Some(a), Some(b)) if graph.nodes.contains_key(a) && graph.nodes.contains_key(b) => Some(find_shortest_path(&graph, a, b)),
+            _ => None
+        };
+        let traversal_root = match inspected {
+            SL::Node(n_k) => Some(n_k),
+            _ => selection.root()
+        }.filter(|&k| graph.nodes.contains_key(k));
+        let traversal = traversal_root
+            .filter(|_| show_traversal)
+            .map(|r_k| graph.traverse(r_k, traversal_kind, traversal_directed));
+        // nodes are colored by BFS level whichever tree is shown
+        let levels = traversal.as_ref().map(|t| graph.bfs(t.root, traversal_directed));
 
 
         egui_macroquad::ui(|ctx| {
@@ -118,6 +283,8 @@ T            - name node";
                         .show(ui, |ui| {
                             ui.checkbox(&mut undirected, "place undirected edges");
                             ui.checkbox(&mut draw_lengths, "draw edge lengths");
+                            ui.checkbox(&mut show_grid, "draw grid");
+                            ui.checkbox(&mut snap_nodes, "snap nodes to grid");
 
                             ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
                                 ui.add(egui::DragValue::new(&mut no_neighbour_spawn_dist).clamp_range(0.0..=500.0).speed(0.05));
@@ -135,6 +302,19 @@ T            - name node";
                                 ui.add(egui::DragValue::new(&mut max_edge_length).clamp_range(min_edge_length..=500.0).speed(0.05));
                                 ui.label("max edge length");
                             });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                if ui.add(egui::DragValue::new(&mut seed).speed(0.2)).changed() {
+                                    rng = StdRng::seed_from_u64(seed);
+                                }
+                                ui.label("N / E generator seed");
+                                if ui.button("restart").clicked() {
+                                    rng = StdRng::seed_from_u64(seed);
+                                }
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::DragValue::new(&mut grid_spacing).clamp_range(1.0..=500.0).speed(0.05));
+                                ui.label("grid spacing");
+                            });
                             ui.label("");
                             if ui.button("default settings").clicked() {
                                 undirected = UNDIRECTED;
@@ -143,29 +323,845 @@ T            - name node";
                                 max_neighbour_spawn_dist = MAX_NEIGHBOUR_SPAWN_DIST;
                                 min_edge_length = MIN_EDGE_LENGTH;
                                 max_edge_length = MAX_EDGE_LENGTH;
+                                show_grid = DRAW_GRID;
+                                snap_nodes = SNAP_TO_GRID;
+                                grid_spacing = GRID_SPACING;
+                                seed = GEN_SEED;
+                                rng = StdRng::seed_from_u64(seed);
                             }
                         });
 
-                    if ui.button("reset graph").clicked() {
-                        graph = Graph::new();
-                        held_sn_k_v = None;
-                        shift_held_sn_k = None;
-                        start_n_k = None;
-                        finish_n_k = None;
-                        selected = SL::None;
-                        names.clear();
-                    }
-                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
-                        ui.label("path: ");
-                        ui.add(egui::TextEdit::singleline(&mut map_png_path).desired_width(f32::INFINITY));
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                            if ui.button("load map").clicked() {
-                                load_map = Some(map_png_path.clone());
+                    egui::CollapsingHeader::new("View")
+                        .show(ui, |ui| {
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                if ui.button("fit graph").clicked() {
+                                    let keys = graph.nodes.keys().collect::<Vec<NodeKey>>();
+                                    fit_nodes(&mut cam, &graph, &keys);
+                                }
+                                if ui.add_enabled(!selection.is_empty(), egui::Button::new("fit selection")).clicked() {
+                                    fit_nodes(&mut cam, &graph, &selection.keys_or_all(&graph, true));
+                                }
+                                if ui.button("reset zoom").clicked() {
+                                    cam.zoom = 2.0/vec2(WIDTH, -HEIGHT);
+                                }
+                            });
+                            ui.checkbox(&mut minimap.visible, "show minimap");
+                        });
+
+                    egui::CollapsingHeader::new("Map")
+                        .show(ui, |ui| {
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.label("path: ");
+                                ui.add(egui::TextEdit::singleline(&mut map_png_path).desired_width(180.0));
+                                if ui.button("load map").clicked() {
+                                    load_map = Some(map_png_path.clone());
+                                }
+                            });
+                            let bg = &mut graph.background;
+                            if map.is_some() {
+                                ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                    ui.add(egui::DragValue::new(&mut bg.offset[0]).speed(0.5));
+                                    ui.add(egui::DragValue::new(&mut bg.offset[1]).speed(0.5));
+                                    ui.label("offset");
+                                });
+                                ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                    ui.add(egui::DragValue::new(&mut bg.scale).clamp_range(0.001..=1000.0).speed(0.001));
+                                    ui.label("scale (units per pixel)");
+                                });
+                                ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                    ui.add(egui::DragValue::new(&mut bg.rotation).clamp_range(-180.0..=180.0).speed(0.1));
+                                    ui.label("rotation (degrees)");
+                                });
+                                ui.add(egui::Slider::new(&mut bg.opacity, 0.0..=1.0).text("opacity"));
+                                ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                    if ui.button("reset placement").clicked() {
+                                        *bg = Background {
+                                            path: bg.path.clone(),
+                                            meters_per_unit: bg.meters_per_unit,
+                                            ..Background::default()
+                                        };
+                                    }
+                                    if ui.button("remove map").clicked() {
+                                        bg.path = None;
+                                        map = None;
+                                    }
+                                });
+                            }
+
+                            // the scale works without a map too, two nodes can serve as the known points
+                            ui.label("");
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                match &mut bg.meters_per_unit {
+                                    Some(m) => {
+                                        ui.add(egui::DragValue::new(m).clamp_range(1e-6..=1e6).speed(0.001));
+                                        ui.label("m per unit");
+                                    }
+                                    None => {
+                                        ui.label("not calibrated");
+                                        if ui.button("set manually").clicked() { bg.meters_per_unit = Some(1.0); }
+                                    }
+                                }
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                let label = if calibrating { "cancel" } else { "pick two points" };
+                                if ui.button(label).clicked() {
+                                    calibrating = !calibrating;
+                                    calibration_points.clear();
+                                }
+                                if ui.add_enabled(bg.meters_per_unit.is_some(), egui::Button::new("clear scale")).clicked() {
+                                    bg.meters_per_unit = None;
+                                }
+                            });
+                            if bg.meters_per_unit.is_some() {
+                                ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                    egui::ComboBox::from_id_source("length_unit")
+                                        .selected_text(graph.units.unit.name())
+                                        .show_ui(ui, |ui| {
+                                            for u in LengthUnit::ALL {
+                                                ui.selectable_value(&mut graph.units.unit, u, u.name());
+                                            }
+                                        });
+                                    ui.add(egui::DragValue::new(&mut graph.units.precision).clamp_range(0..=6).speed(0.05));
+                                    ui.label("units / decimals");
+                                });
+                            }
+                            if let [a, b] = calibration_points[..] {
+                                ui.label(format!("picked distance: {:.1} units", a.distance(b)));
+                                ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                    ui.add(egui::DragValue::new(&mut calibration_distance).clamp_range(0.001..=1e7).speed(0.5));
+                                    ui.label("real distance (m)");
+                                    if ui.button("apply").clicked() && bg.calibrate(a, b, calibration_distance) {
+                                        calibrating = false;
+                                        calibration_points.clear();
+                                    }
+                                });
+                            }
+                            else if calibrating {
+                                ui.label("click two points with a known distance");
+                            }
+                        });
+
+                    egui::CollapsingHeader::new("Path")
+                        .show(ui, |ui| {
+                            match &path {
+                                Some((n_path, e_path, d)) if n_path.len() > 1 => {
+                                    let total = graph.format_length(*d);
+                                    egui::Grid::new("path")
+                                        .num_columns(2)
+                                        .striped(true)
+                                        .show(ui, |ui| {
+                                            ui.label("total cost"); ui.label(&total); ui.end_row();
+                                            ui.label("hops"); ui.label(e_path.len().to_string()); ui.end_row();
+                                        });
+
+                                    let route = n_path
+                                        .iter()
+                                        .map(|&n_k| node_name(&names, n_k))
+                                        .collect::<Vec<String>>();
+                                    ui.label("route");
+                                    egui::ScrollArea::vertical()
+                                        .id_source("route")
+                                        .max_height(200.0)
+                                        .show(ui, |ui| {
+                                            for (i, name) in route.iter().enumerate() {
+                                                ui.label(format!("{}. {}", i + 1, name));
+                                            }
+                                        });
+                                    if ui.button("copy route").clicked() {
+                                        let text = format!("{}\ntotal: {}, hops: {}", route.join(" -> "), total, e_path.len());
+                                        ui.output_mut(|o| o.copied_text = text);
+                                    }
+                                }
+                                Some(_) => {
+                                    ui.colored_label(Color32::LIGHT_RED, "no path from start to finish");
+                                }
+                                None => {
+                                    ui.label("set start (S) and finish (F) nodes");
+                                }
                             }
+                        });
 
+                    egui::CollapsingHeader::new("Algorithm steps")
+                        .show(ui, |ui| {
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                for a in Algorithm::ALL {
+                                    // a recording only makes sense under the algorithm it was made with
+                                    if ui.radio_value(&mut algo_player.algorithm, a, a.name()).changed() {
+                                        algo_player.clear();
+                                    }
+                                }
+                            });
+                            let start = start_n_k.filter(|&k| graph.nodes.contains_key(k));
+                            let finish = finish_n_k.filter(|&k| graph.nodes.contains_key(k));
+                            if ui.add_enabled(start.is_some(), egui::Button::new("record from start")).clicked() {
+                                algo_player.record(&graph, start.unwrap(), finish, weight_attr.as_deref());
+                                algo_player.playing = true;
+                            }
+                            ui.label(format!("edge weights: {} (see statistics)", weight_attr.as_deref().unwrap_or("length")));
+                            if !algo_player.is_active() {
+                                ui.label("set a start (S) and optionally a finish (F) node");
+                                return;
+                            }
 
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                if ui.button("|<").clicked() { algo_player.index = 0; }
+                                if ui.button("<").clicked() { algo_player.step_back(); }
+                                let label = if algo_player.playing { "pause" } else { "play" };
+                                if ui.button(label).clicked() {
+                                    if algo_player.is_at_end() { algo_player.index = 0; }
+                                    algo_player.playing = !algo_player.playing;
+                                }
+                                if ui.button(">").clicked() { algo_player.step_forward(); }
+                                if ui.button("clear").clicked() { algo_player.clear(); }
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::DragValue::new(&mut algo_player.speed).clamp_range(0.25..=60.0).speed(0.05));
+                                ui.label("steps per second");
+                            });
+
+                            if let Some(step) = algo_player.current() {
+                                egui::Grid::new("algorithm_step")
+                                    .num_columns(2)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        ui.label("step"); ui.label(format!("{} / {}", algo_player.index, algo_player.steps.len() - 1)); ui.end_row();
+                                        ui.label("current"); ui.label(step.current.map_or("-".to_string(), |n_k| node_name(&names, n_k))); ui.end_row();
+                                        ui.label("frontier"); ui.label(step.frontier.len().to_string()); ui.end_row();
+                                        ui.label("visited"); ui.label(step.visited.len().to_string()); ui.end_row();
+                                    });
+                                if step.finished {
+                                    let d = algo_player.finish.and_then(|f_k| step.dist.get(f_k)).copied().unwrap_or(0.0);
+                                    ui.label(format!("finish reached, distance {}", algo_player.format_dist(&graph, d)));
+                                }
+                                else if algo_player.is_at_end() && algo_player.finish.is_some() {
+                                    ui.colored_label(Color32::LIGHT_RED, "finish is not reachable");
+                                }
+                            }
+                        });
+
+                    egui::CollapsingHeader::new("Traversal")
+                        .show(ui, |ui| {
+                            ui.checkbox(&mut show_traversal, "show traversal tree (L)");
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.radio_value(&mut traversal_kind, TraversalKind::Bfs, "BFS");
+                                ui.radio_value(&mut traversal_kind, TraversalKind::Dfs, "DFS");
+                            });
+                            ui.checkbox(&mut traversal_directed, "follow edge directions");
+                            let (Some(t), Some(l)) = (&traversal, &levels) else {
+                                ui.label("click a node to use it as the root");
+                                return;
+                            };
+
+                            egui::Grid::new("traversal")
+                                .num_columns(2)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("root"); ui.label(node_name(&names, t.root)); ui.end_row();
+                                    ui.label("reached"); ui.label(format!("{} / {}", t.order.len(), graph.nodes.len())); ui.end_row();
+                                    ui.label("tree depth"); ui.label(t.max_depth().to_string()); ui.end_row();
+                                    ui.label("BFS levels"); ui.label((l.max_depth() + 1).to_string()); ui.end_row();
+                                });
+                            egui::ScrollArea::vertical()
+                                .id_source("traversal_order")
+                                .max_height(150.0)
+                                .show(ui, |ui| {
+                                    for (i, &n_k) in t.order.iter().enumerate() {
+                                        ui.label(format!("{}. {} (level {})", i + 1, node_name(&names, n_k), l.depth[n_k]));
+                                    }
+                                });
+                        });
+
+                    egui::CollapsingHeader::new("Search")
+                        .show(ui, |ui| {
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::TextEdit::singleline(&mut search_query)
+                                    .hint_text("name, value or attr=value")
+                                    .desired_width(180.0));
+                                if ui.small_button("x").clicked() { search_query.clear(); }
+                            });
+                            ui.checkbox(&mut search_filter, "dim elements that do not match");
+
+                            let hits = search_nodes(&graph, &names, &search_query);
+                            if !search_query.trim().is_empty() {
+                                ui.label(format!("{} matches", hits.len()));
+                            }
+                            egui::ScrollArea::vertical()
+                                .max_height(200.0)
+                                .show(ui, |ui| {
+                                    for hit in hits.iter().take(SEARCH_MAX_HITS) {
+                                        if ui.selectable_label(inspected == SL::Node(hit.key), &hit.text).clicked() {
+                                            cam.target = graph.nodes[hit.key].get_p_v();
+                                            inspected = SL::Node(hit.key);
+                                        }
+                                    }
+                                });
+                        });
+
+                    egui::CollapsingHeader::new("Inspector")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            // the hovered element takes precedence over the last clicked one
+                            let shown = match selected {
+                                SL::None => inspected,
+                                _ if mouse_over_ui => inspected,
+                                _ => selected
+                            };
+                            let node_label = |n_k: NodeKey| node_name(&names, n_k);
+
+                            let element = match shown {
+                                SL::Node(n_k) if graph.nodes.contains_key(n_k) => {
+                                    let keys = graph.nodes[n_k].get_keys();
+                                    let (n_in, n_out) = (keys.heads.len(), keys.tails.len());
+                                    let mut v = graph.nodes[n_k].get_p_v();
+
+                                    ui.label(format!("node {}", node_label(n_k)));
+                                    egui::Grid::new("inspector_node")
+                                        .num_columns(2)
+                                        .striped(true)
+                                        .show(ui, |ui| {
+                                            ui.label("name");
+                                            match names.get_mut(n_k) {
+                                                Some(name) => { ui.text_edit_singleline(&mut **name); }
+                                                None => if ui.button("add name").clicked() {
+                                                    names.insert(n_k, Box::new(String::from("Name")));
+                                                }
+                                            }
+                                            ui.end_row();
+                                            ui.label("x");
+                                            let x = ui.add(egui::DragValue::new(&mut v.x).speed(0.5)).changed();
+                                            ui.end_row();
+                                            ui.label("y");
+                                            let y = ui.add(egui::DragValue::new(&mut v.y).speed(0.5)).changed();
+                                            ui.end_row();
+                                            if x || y {
+                                                graph.set_node_pos(n_k, &v);
+                                            }
+                                            ui.label("in / out edges"); ui.label(format!("{} / {}", n_in, n_out)); ui.end_row();
+                                            ui.label("neighbours"); ui.label(graph.degree(n_k).to_string()); ui.end_row();
+                                            ui.label("pinned"); ui.label(force_layout.pinned.contains(&n_k).to_string()); ui.end_row();
+                                        });
+                                    Some(shown)
+                                }
+                                SL::Edge(e_k) if graph.edges.contains_key(e_k) => {
+                                    let e = &graph.edges[e_k];
+                                    let (from, to) = (e.get_keys().from, e.get_keys().to);
+                                    let length = graph.format_length(e.get_length());
+                                    let directed = from == to || graph.find_edge(to, from).is_none();
+                                    let shape = match e.get_shape() {
+                                        EdgeShape::Straight => "straight",
+                                        EdgeShape::Curved(_) => "curved",
+                                        EdgeShape::Polyline(_) => "polyline"
+                                    };
+
+                                    ui.label(format!("edge {:?}", e_k));
+                                    egui::Grid::new("inspector_edge")
+                                        .num_columns(2)
+                                        .striped(true)
+                                        .show(ui, |ui| {
+                                            for (label, n_k) in [("from", from), ("to", to)] {
+                                                let mut v = graph.nodes[n_k].get_p_v();
+                                                ui.label(label);
+                                                ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                                    let x = ui.add(egui::DragValue::new(&mut v.x).speed(0.5)).changed();
+                                                    let y = ui.add(egui::DragValue::new(&mut v.y).speed(0.5)).changed();
+                                                    if x || y {
+                                                        graph.set_node_pos(n_k, &v);
+                                                    }
+                                                    ui.label(node_label(n_k));
+                                                });
+                                                ui.end_row();
+                                            }
+                                            ui.label("length"); ui.label(length); ui.end_row();
+                                            ui.label("directed"); ui.label(directed.to_string()); ui.end_row();
+                                            ui.label("shape"); ui.label(shape); ui.end_row();
+                                        });
+                                    Some(shown)
+                                }
+                                _ => {
+                                    ui.label("click a node or an edge to inspect it");
+                                    None
+                                }
+                            };
+
+                            if let Some(element) = element {
+                                let (target, own) = match element {
+                                    SL::Node(n_k) => (AttrTarget::Node, graph.nodes[n_k].get_attributes().clone()),
+                                    SL::Edge(e_k) => (AttrTarget::Edge, graph.edges[e_k].get_attributes().clone()),
+                                    SL::None => unreachable!()
+                                };
+                                let defs = graph.schema.defs(target).clone();
+                                // schema attributes first, then the ones set only on this element
+                                let attr_names = defs.keys()
+                                    .chain(own.keys().filter(|k| !defs.contains_key(*k)))
+                                    .cloned()
+                                    .collect::<Vec<String>>();
+                                let mut changed = None;
+                                let mut removed = None;
+
+                                ui.label("");
+                                ui.label("attributes");
+                                egui::Grid::new("inspector_attributes")
+                                    .num_columns(3)
+                                    .show(ui, |ui| {
+                                        for name in &attr_names {
+                                            let mut value = match own.get(name) {
+                                                Some(v) if graph.schema.accepts(target, name, v) => v.clone(),
+                                                _ => defs[name].clone()
+                                            };
+                                            ui.label(name);
+                                            if attr_value_ui(ui, &mut value) { changed = Some((name.clone(), value)); }
+                                            match own.contains_key(name) {
+                                                true => if ui.small_button("x").clicked() { removed = Some(name.clone()); }
+                                                false => { ui.label("default"); }
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+
+                                ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                    ui.add(egui::TextEdit::singleline(&mut new_attr_key).desired_width(100.0));
+                                    let key = new_attr_key.trim().to_string();
+                                    let defined = defs.get(&key);
+                                    if defined.is_none() {
+                                        egui::ComboBox::from_id_source("new_attr_type")
+                                            .selected_text(new_attr_type.name())
+                                            .show_ui(ui, |ui| {
+                                                for t in AttrType::ALL {
+                                                    ui.selectable_value(&mut new_attr_type, t, t.name());
+                                                }
+                                            });
+                                    }
+                                    let valid = !key.is_empty() && !own.contains_key(&key);
+                                    if ui.add_enabled(valid, egui::Button::new("add attribute")).clicked() {
+                                        let value = defined.cloned().unwrap_or_else(|| new_attr_type.default_value());
+                                        changed = Some((key, value));
+                                        new_attr_key.clear();
+                                    }
+                                });
+
+                                match element {
+                                    SL::Node(n_k) => {
+                                        if let Some((name, value)) = changed { graph.set_node_attr(n_k, &name, value); }
+                                        if let Some(name) = removed { graph.remove_node_attr(n_k, &name); }
+                                    }
+                                    SL::Edge(e_k) => {
+                                        if let Some((name, value)) = changed { graph.set_edge_attr(e_k, &name, value); }
+                                        if let Some(name) = removed { graph.remove_edge_attr(e_k, &name); }
+                                    }
+                                    SL::None => {}
+                                }
+
+                                ui.label("");
+                                ui.label("style");
+                                ui.push_id("inspector_style", |ui| match element {
+                                    SL::Node(n_k) => {
+                                        let mut style = graph.nodes[n_k].get_style().clone();
+                                        if node_style_ui(ui, &mut style) { graph.set_node_style(n_k, style); }
+                                    }
+                                    SL::Edge(e_k) => {
+                                        let mut style = graph.edges[e_k].get_style().clone();
+                                        if edge_style_ui(ui, &mut style) { graph.set_edge_style(e_k, style); }
+                                    }
+                                    SL::None => {}
+                                });
+                            }
+                        });
+
+                    egui::CollapsingHeader::new("Attribute schema")
+                        .show(ui, |ui| {
+                            let mut undefine = None;
+                            let mut redefine = None;
+
+                            for (target, label) in [(AttrTarget::Node, "node attributes"), (AttrTarget::Edge, "edge attributes")] {
+                                ui.label(label);
+                                egui::Grid::new(label)
+                                    .num_columns(4)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for (name, default) in graph.schema.defs(target) {
+                                            let mut value = default.clone();
+                                            ui.label(name);
+                                            ui.label(default.get_type().name());
+                                            if attr_value_ui(ui, &mut value) { redefine = Some((target, name.clone(), value)); }
+                                            if ui.small_button("x").clicked() { undefine = Some((target, name.clone())); }
+                                            ui.end_row();
+                                        }
+                                    });
+                            }
+                            if let Some((target, name, value)) = redefine {
+                                graph.define_attr(target, &name, value);
+                            }
+                            if let Some((target, name)) = undefine {
+                                graph.undefine_attr(target, &name);
+                            }
+
+                            ui.label("");
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::TextEdit::singleline(&mut new_def_name).desired_width(100.0));
+                                egui::ComboBox::from_id_source("new_def_type")
+                                    .selected_text(new_def_type.name())
+                                    .show_ui(ui, |ui| {
+                                        for t in AttrType::ALL {
+                                            ui.selectable_value(&mut new_def_type, t, t.name());
+                                        }
+                                    });
+                                ui.radio_value(&mut new_def_target, AttrTarget::Node, "node");
+                                ui.radio_value(&mut new_def_target, AttrTarget::Edge, "edge");
+                            });
+                            let name = new_def_name.trim().to_string();
+                            let valid = !name.is_empty() && !graph.schema.defs(new_def_target).contains_key(&name);
+                            if ui.add_enabled(valid, egui::Button::new("define attribute")).clicked() {
+                                graph.define_attr(new_def_target, &name, new_def_type.default_value());
+                                new_def_name.clear();
+                            }
+                        });
+
+                    egui::CollapsingHeader::new("Style rules")
+                        .show(ui, |ui| {
+                            ui.label("later rules win, element styles win over rules");
+                            let mut removed = None;
+
+                            for i in 0..graph.style_rules().len() {
+                                let mut rule = graph.style_rules()[i].clone();
+                                let mut changed = false;
+
+                                ui.push_id(i, |ui| {
+                                    ui.separator();
+                                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                        changed |= ui.radio_value(&mut rule.target, AttrTarget::Node, "node").changed();
+                                        changed |= ui.radio_value(&mut rule.target, AttrTarget::Edge, "edge").changed();
+                                        if ui.small_button("remove").clicked() { removed = Some(i); }
+                                    });
+                                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                        changed |= ui.add(egui::TextEdit::singleline(&mut rule.attr).desired_width(80.0)).changed();
+                                        egui::ComboBox::from_id_source("op")
+                                            .width(60.0)
+                                            .selected_text(rule.op.name())
+                                            .show_ui(ui, |ui| {
+                                                for op in RuleOp::ALL {
+                                                    changed |= ui.selectable_value(&mut rule.op, op, op.name()).changed();
+                                                }
+                                            });
+                                        if rule.op != RuleOp::Exists {
+                                            let mut t = rule.value.get_type();
+                                            egui::ComboBox::from_id_source("value_type")
+                                                .width(60.0)
+                                                .selected_text(t.name())
+                                                .show_ui(ui, |ui| {
+                                                    for at in AttrType::ALL {
+                                                        ui.selectable_value(&mut t, at, at.name());
+                                                    }
+                                                });
+                                            if t != rule.value.get_type() {
+                                                rule.value = t.default_value();
+                                                changed = true;
+                                            }
+                                            changed |= attr_value_ui(ui, &mut rule.value);
+                                        }
+                                    });
+                                    changed |= match rule.target {
+                                        AttrTarget::Node => node_style_ui(ui, &mut rule.node),
+                                        AttrTarget::Edge => edge_style_ui(ui, &mut rule.edge)
+                                    };
+                                });
+                                if changed { graph.set_style_rule(i, rule); }
+                            }
+                            if let Some(i) = removed {
+                                graph.remove_style_rule(i);
+                            }
+
+                            ui.separator();
+                            if ui.button("add rule").clicked() {
+                                graph.add_style_rule(StyleRule::new(AttrTarget::Node, ""));
+                            }
+                        });
+
+                    egui::CollapsingHeader::new("Statistics")
+                        .show(ui, |ui| {
+                            let weights = graph.schema.numeric(AttrTarget::Edge);
+                            if weight_attr.as_ref().is_some_and(|w| !weights.contains(w)) { weight_attr = None; }
+                            egui::ComboBox::from_label("edge weight")
+                                .selected_text(weight_attr.as_deref().unwrap_or("length"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut weight_attr, None, "length");
+                                    for w in weights {
+                                        ui.selectable_value(&mut weight_attr, Some(w.clone()), w);
+                                    }
+                                });
+
+                            let s = match stats {
+                                Some(ref s) if !s.is_outdated(&graph, weight_attr.as_deref()) => s,
+                                _ => stats.insert(GraphStats::new(&graph, weight_attr.as_deref()))
+                            };
+                            // weighted distances are not lengths
+                            let opt = |v: Option<f32>| match (v, &weight_attr) {
+                                (None, _) => "-".to_string(),
+                                (Some(v), None) => graph.format_length(v),
+                                (Some(v), Some(_)) => format!("{:.1}", v)
+                            };
+
+                            egui::Grid::new("statistics")
+                                .num_columns(2)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("nodes"); ui.label(s.nodes.to_string()); ui.end_row();
+                                    ui.label("edges"); ui.label(s.edges.to_string()); ui.end_row();
+                                    ui.label("density"); ui.label(format!("{:.4}", s.density)); ui.end_row();
+                                    ui.label("average degree"); ui.label(format!("{:.2}", s.avg_degree)); ui.end_row();
+                                    ui.label("min / max degree"); ui.label(format!("{} / {}", s.min_degree, s.max_degree)); ui.end_row();
+                                    // unreachable pairs make them infinite
+                                    let inf = |v: Option<f32>| match (v, s.connected) {
+                                        (None, false) => "∞ (disconnected)".to_string(),
+                                        _ => opt(v)
+                                    };
+                                    ui.label("diameter"); ui.label(inf(s.diameter)); ui.end_row();
+                                    ui.label("radius"); ui.label(inf(s.radius)); ui.end_row();
+                                    ui.label(match s.connected {
+                                        true => "average path length",
+                                        false => "average path length (reachable pairs)"
+                                    }); ui.label(opt(s.avg_path_length)); ui.end_row();
+                                    ui.label("clustering coefficient"); ui.label(format!("{:.3}", s.clustering)); ui.end_row();
+                                    ui.label("total edge length"); ui.label(graph.format_length(s.total_length)); ui.end_row();
+                                });
+                        });
+
+                    egui::CollapsingHeader::new("Layout")
+                        .show(ui, |ui| {
+                            ui.label("force-directed");
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                let label = if force_layout.running { "pause" } else { "run" };
+                                if ui.button(label).clicked() {
+                                    if !force_layout.running { pos_history.push(&graph); }
+                                    force_layout.running = !force_layout.running;
+                                    if force_layout.converged() { force_layout.restart(); }
+                                }
+                                if ui.button("step").clicked() {
+                                    pos_history.push(&graph);
+                                    step_layout = true;
+                                }
+                                if ui.button("restart").clicked() {
+                                    pos_history.push(&graph);
+                                    force_layout.restart();
+                                    force_layout.running = true;
+                                }
+                                if ui.button("unpin all").clicked() {
+                                    force_layout.pinned.clear();
+                                }
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::DragValue::new(&mut force_layout.ideal_length).clamp_range(5.0..=500.0).speed(0.1));
+                                ui.label("ideal edge length");
+                            });
+                            ui.label("");
+                            if ui.button("layered (directed)").clicked() {
+                                pos_history.push(&graph);
+                                force_layout.running = false;
+                                layered_layout(&mut graph, LAYER_NODE_SPACING, LAYER_SPACING);
+                            }
+                            if ui.button("curve reciprocal edges").clicked() {
+                                graph.curve_reciprocal_edges();
+                            }
+
+                            ui.label("");
+                            ui.checkbox(&mut layout_selection_only, "apply to selection only");
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::DragValue::new(&mut layout_spacing).clamp_range(5.0..=500.0).speed(0.1));
+                                ui.label("spacing");
+                            });
+                            let keys = selection.keys_or_all(&graph, layout_selection_only);
+
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                if ui.button("circular").clicked() {
+                                    let ordered = match circle_order {
+                                        CircleOrder::Component => order_by_component(&graph, &keys),
+                                        CircleOrder::Name => order_by_name(&keys, &names)
+                                    };
+                                    pos_history.push(&graph);
+                                    circular_layout(&mut graph, &ordered, layout_spacing);
+                                }
+                                ui.radio_value(&mut circle_order, CircleOrder::Component, "by component");
+                                ui.radio_value(&mut circle_order, CircleOrder::Name, "by name");
+                            });
+                            if ui.button("grid").clicked() {
+                                let ordered = order_by_component(&graph, &keys);
+                                pos_history.push(&graph);
+                                grid_layout(&mut graph, &ordered, layout_spacing);
+                            }
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                let root = selection.root();
+                                if ui.add_enabled(root.is_some(), egui::Button::new("tree")).clicked() {
+                                    pos_history.push(&graph);
+                                    tree_layout(&mut graph, root.unwrap(), &keys, layout_spacing, tree_style);
+                                }
+                                ui.radio_value(&mut tree_style, TreeStyle::Tidy, "tidy");
+                                ui.radio_value(&mut tree_style, TreeStyle::Radial, "radial");
+                            });
                         });
-                    });
+
+                    egui::CollapsingHeader::new("Align")
+                        .show(ui, |ui| {
+                            let keys = selection.keys_or_all(&graph, true);
+                            let enough = selection.nodes.len() > 1;
+                            let mut alignment = None;
+
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                if ui.add_enabled(enough, egui::Button::new("left")).clicked() { alignment = Some(Alignment::Left); }
+                                if ui.add_enabled(enough, egui::Button::new("center")).clicked() { alignment = Some(Alignment::Center); }
+                                if ui.add_enabled(enough, egui::Button::new("right")).clicked() { alignment = Some(Alignment::Right); }
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                if ui.add_enabled(enough, egui::Button::new("top")).clicked() { alignment = Some(Alignment::Top); }
+                                if ui.add_enabled(enough, egui::Button::new("middle")).clicked() { alignment = Some(Alignment::Middle); }
+                                if ui.add_enabled(enough, egui::Button::new("bottom")).clicked() { alignment = Some(Alignment::Bottom); }
+                            });
+                            if let Some(alignment) = alignment {
+                                pos_history.push(&graph);
+                                align_nodes(&mut graph, &keys, alignment);
+                            }
+
+                            let enough = selection.nodes.len() > 2;
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                if ui.add_enabled(enough, egui::Button::new("distribute horizontally")).clicked() {
+                                    pos_history.push(&graph);
+                                    distribute_nodes(&mut graph, &keys, true);
+                                }
+                                if ui.add_enabled(enough, egui::Button::new("distribute vertically")).clicked() {
+                                    pos_history.push(&graph);
+                                    distribute_nodes(&mut graph, &keys, false);
+                                }
+                            });
+                            if ui.add_enabled(!selection.is_empty(), egui::Button::new("snap selection to grid")).clicked() {
+                                pos_history.push(&graph);
+                                for &n_k in &keys {
+                                    let v = snap_to_grid(graph.nodes[n_k].get_p_v(), grid_spacing);
+                                    graph.set_node_pos(n_k, &v);
+                                }
+                            }
+                        });
+
+                    egui::CollapsingHeader::new("Generators")
+                        .show(ui, |ui| {
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.radio_value(&mut gen_model, GraphModel::ErdosRenyi, "Erdős–Rényi");
+                                ui.radio_value(&mut gen_model, GraphModel::BarabasiAlbert, "Barabási–Albert");
+                                ui.radio_value(&mut gen_model, GraphModel::WattsStrogatz, "Watts–Strogatz");
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::DragValue::new(&mut gen_n).clamp_range(0..=2000).speed(0.2));
+                                ui.label("nodes (n)");
+                            });
+                            match gen_model {
+                                GraphModel::ErdosRenyi => {
+                                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                        ui.add(egui::DragValue::new(&mut gen_p).clamp_range(0.0..=1.0).speed(0.005));
+                                        ui.label("edge probability (p)");
+                                    });
+                                }
+                                GraphModel::BarabasiAlbert => {
+                                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                        ui.add(egui::DragValue::new(&mut gen_m).clamp_range(1..=20).speed(0.05));
+                                        ui.label("edges per new node (m)");
+                                    });
+                                }
+                                GraphModel::WattsStrogatz => {
+                                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                        ui.add(egui::DragValue::new(&mut gen_k).clamp_range(2..=40).speed(0.05));
+                                        ui.label("ring neighbours (k)");
+                                    });
+                                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                        ui.add(egui::DragValue::new(&mut gen_beta).clamp_range(0.0..=1.0).speed(0.005));
+                                        ui.label("rewiring probability (beta)");
+                                    });
+                                }
+                            }
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::DragValue::new(&mut gen_seed).speed(0.2));
+                                ui.label("seed");
+                            });
+                            if ui.button("generate (replaces graph)").clicked() {
+                                new_graph = Some(match gen_model {
+                                    GraphModel::ErdosRenyi => erdos_renyi(gen_n, gen_p, undirected, gen_seed),
+                                    GraphModel::BarabasiAlbert => barabasi_albert(gen_n, gen_m, undirected, gen_seed),
+                                    GraphModel::WattsStrogatz => watts_strogatz(gen_n, gen_k, gen_beta, undirected, gen_seed)
+                                });
+                                cam.target = Vec2::ZERO;
+                            }
+
+                            ui.label("");
+                            ui.label("structures, press G to place at cursor");
+                            egui::ComboBox::from_id_source("structure")
+                                .selected_text(structure.name())
+                                .show_ui(ui, |ui| {
+                                    for st in Structure::ALL {
+                                        ui.selectable_value(&mut structure, st, st.name());
+                                    }
+                                });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                let max_size = match structure {
+                                    Structure::BinaryTree => 10,
+                                    Structure::SquareLattice | Structure::HexagonalLattice | Structure::TriangularLattice => 40,
+                                    _ => 200
+                                };
+                                ui.add(egui::DragValue::new(&mut structure_size).clamp_range(1..=max_size).speed(0.05));
+                                ui.label(structure.size_label());
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.add(egui::DragValue::new(&mut structure_spacing).clamp_range(5.0..=500.0).speed(0.1));
+                                ui.label("spacing");
+                            });
+
+                            ui.label("");
+                            ui.label("scatter nodes in region");
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.radio_value(&mut region_shape, RegionShape::Rectangle, "rectangle");
+                                ui.radio_value(&mut region_shape, RegionShape::Polygon, "polygon");
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                if ui.button("draw region").clicked() {
+                                    region = Region::new();
+                                    drawing_region = true;
+                                }
+                                if ui.add_enabled(region.is_valid() && !drawing_region, egui::Button::new("scatter")).clicked() {
+                                    scatter_nodes(&mut graph, &region, no_neighbour_spawn_dist, &mut rng);
+                                }
+                                if ui.button("clear region").clicked() {
+                                    region = Region::new();
+                                    drawing_region = false;
+                                }
+                            });
+                            if drawing_region {
+                                ui.label(match region_shape {
+                                    RegionShape::Rectangle => "drag LMB to draw the rectangle",
+                                    RegionShape::Polygon => "LMB adds vertices, RMB or Enter closes"
+                                });
+                            }
+
+                            ui.label("");
+                            ui.label("connect existing nodes");
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.radio_value(&mut proximity, Proximity::Delaunay, "Delaunay");
+                                ui.radio_value(&mut proximity, Proximity::Gabriel, "Gabriel");
+                                ui.radio_value(&mut proximity, Proximity::RelativeNeighbourhood, "relative neighbourhood");
+                            });
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                                ui.radio_value(&mut proximity, Proximity::Nearest, "k nearest");
+                                ui.add_enabled(proximity == Proximity::Nearest, egui::DragValue::new(&mut nearest_k).clamp_range(1..=20).speed(0.05));
+                            });
+                            ui.checkbox(&mut connect_selection_only, "selection only");
+                            ui.checkbox(&mut connect_length_bounds, "respect min / max edge length");
+                            if ui.button("connect").clicked() {
+                                let keys = selection.keys_or_all(&graph, connect_selection_only);
+                                let (mn, mx) = match connect_length_bounds {
+                                    true => (min_edge_length, max_edge_length),
+                                    false => (0.0, f32::INFINITY)
+                                };
+                                connect_proximity(&mut graph, &keys, proximity, nearest_k, mn, mx, undirected);
+                            }
+                        });
+
+                    if ui.button("reset graph").clicked() {
+                        new_graph = Some(Graph::new());
+                    }
 
 
                 });
@@ -191,13 +1187,153 @@ T            - name node";
         });
 
 
+        let map_rect = map.as_ref().map(|t| graph.background.bounds(t));
+        minimap.update_bounds(&graph, map_rect, &cam);
+        mouse_over_ui |= minimap.handle_input(
+            &mut cam,
+            is_mouse_button_pressed(MouseButton::Left) && !mouse_over_ui,
+            is_mouse_button_down(MouseButton::Left),
+            Vec2::from(mouse_position())
+        );
+
+
+        if let Some(g) = new_graph {
+            let background = graph.background.clone();
+            graph = g;
+            graph.background = background;
+            held_sn_k_v = None;
+            shift_held_sn_k = None;
+            held_handle = None;
+            inspected = SL::None;
+            start_n_k = None;
+            finish_n_k = None;
+            selected = SL::None;
+            names.clear();
+            stats = None;
+            force_layout.pinned.clear();
+            force_layout.running = false;
+            pos_history.clear();
+            selection.clear();
+            algo_player.clear();
+            region = Region::new();
+            drawing_region = false;
+            rng = StdRng::seed_from_u64(seed);
+        }
+
+        if !is_mouse_button_down(MouseButton::Right) { rmb_consumed = false; }
+
         if !mouse_over_ui {
+            if is_mouse_button_pressed(MouseButton::Left) && selected != SL::None {
+                inspected = selected;
+            }
+
+            if is_key_pressed(KeyCode::L) {
+                show_traversal = !show_traversal;
+            }
+
             if is_key_pressed(KeyCode::T) {
                 if let SL::Node(sn_k) = selected {
                     names.insert(sn_k, Box::new(String::from("Name")));
                 }
             }
 
+            if is_key_down(KeyCode::LeftControl) && is_key_pressed(KeyCode::Z) {
+                force_layout.running = false;
+                pos_history.undo(&mut graph);
+            }
+
+            if is_key_pressed(KeyCode::R) {
+                match is_key_down(KeyCode::LeftShift) {
+                    true => {
+                        for e_k in graph.edges_between(&selection.nodes) {
+                            graph.reverse_edge(e_k);
+                        }
+                    }
+                    false => if let SL::Edge(se_k) = selected {
+                        graph.reverse_edge(se_k);
+                    }
+                }
+            }
+
+            if is_key_pressed(KeyCode::D) {
+                if let SL::Edge(se_k) = selected {
+                    graph.toggle_directed(se_k);
+                    selected = SL::None;
+                }
+            }
+
+            if is_key_pressed(KeyCode::V) {
+                if let SL::Edge(se_k) = selected {
+                    let shape = match graph.edges[se_k].get_shape() {
+                        EdgeShape::Curved(_) => EdgeShape::Straight,
+                        EdgeShape::Straight | EdgeShape::Polyline(_) => EdgeShape::Curved([0.5, CURVE_OFFSET])
+                    };
+                    graph.set_edge_shape(se_k, shape);
+                }
+            }
+
+            if is_key_pressed(KeyCode::B) {
+                if let SL::Edge(se_k) = selected {
+                    if !graph.edges[se_k].is_loop() {
+                        graph.add_edge_bend(se_k, &gm_v);
+                    }
+                }
+            }
+
+            if is_key_pressed(KeyCode::I) {
+                if let SL::Edge(se_k) = selected {
+                    if !graph.edges[se_k].is_loop() {
+                        let v = graph.project_on_edge(se_k, &m_v);
+                        graph.split_edge(se_k, &v);
+                        selected = SL::None;
+                    }
+                }
+            }
+
+            if is_key_pressed(KeyCode::C) {
+                if let SL::Edge(se_k) = selected {
+                    let other = graph.edges[se_k].get_keys().to;
+                    let keep = graph.contract_edge(se_k);
+                    if let Some(name) = names.remove(other) {
+                        if !names.contains_key(keep) { names.insert(keep, name); }
+                    }
+                    selection.retain_existing(&graph);
+                    force_layout.retain_existing(&graph);
+                    selected = SL::None;
+                    held_sn_k_v = None;
+                    shift_held_sn_k = None;
+                }
+            }
+
+            if is_key_pressed(KeyCode::M) && selection.nodes.len() > 1 {
+                let keys = selection.keys_or_all(&graph, true);
+                let keep = selection.root().unwrap();
+                let v = keys.iter().fold(Vec2::ZERO, |acc, &k| acc + graph.nodes[k].get_p_v()) / keys.len() as f32;
+
+                for n_k in keys.into_iter().filter(|&k| k != keep) {
+                    graph.merge_nodes(keep, n_k);
+                    if let Some(name) = names.remove(n_k) {
+                        if !names.contains_key(keep) { names.insert(keep, name); }
+                    }
+                }
+                graph.set_node_pos(keep, &v);
+                selection.retain_existing(&graph);
+                force_layout.retain_existing(&graph);
+                selected = SL::None;
+                held_sn_k_v = None;
+                shift_held_sn_k = None;
+            }
+
+            if is_key_pressed(KeyCode::G) {
+                place_structure(&mut graph, structure, structure_size, structure_spacing, gm_v, undirected);
+            }
+
+            if is_key_pressed(KeyCode::P) {
+                if let SL::Node(sn_k) = selected {
+                    force_layout.toggle_pin(sn_k);
+                }
+            }
+
 
 
 
@@ -231,10 +1367,40 @@ T            - name node";
             }
             //Shift + LMB
             else if !is_key_down(KeyCode::LeftControl) {
-                if is_key_down(KeyCode::LeftShift) && is_mouse_button_pressed(MouseButton::Left) {
-                    if let SL::Node(sn_k) = selected {
-                        graph.set_node_pos(sn_k, &m_v);
-                        shift_held_sn_k = Some(sn_k);
+                if calibrating {
+                    if is_mouse_button_pressed(MouseButton::Left) && calibration_points.len() < 2 {
+                        calibration_points.push(m_v);
+                    }
+                }
+
+                else if drawing_region {
+                    if is_mouse_button_pressed(MouseButton::Left) {
+                        match region_shape {
+                            RegionShape::Rectangle => region_rect_v = Some(gm_v),
+                            RegionShape::Polygon => region.points.push(gm_v)
+                        }
+                    }
+                    if region_shape == RegionShape::Polygon &&
+                        (is_key_pressed(KeyCode::Enter) || is_mouse_button_pressed(MouseButton::Right)) {
+                        drawing_region = false;
+                        rmb_consumed = is_mouse_button_down(MouseButton::Right);
+                    }
+                }
+
+                else if is_key_down(KeyCode::LeftAlt) && is_mouse_button_pressed(MouseButton::Left) {
+                    match selected {
+                        SL::Node(sn_k) => selection.toggle(sn_k),
+                        SL::Edge(_) | SL::None => rect_sel_v = Some(m_v)
+                    }
+                }
+
+                else if is_key_down(KeyCode::LeftShift) && is_mouse_button_pressed(MouseButton::Left) {
+                    match graph.edge_handle_at(&m_v) {
+                        Some(handle) => held_handle = Some(handle),
+                        None => if let SL::Node(sn_k) = selected {
+                            graph.set_node_pos(sn_k, &gm_v);
+                            shift_held_sn_k = Some(sn_k);
+                        }
                     }
                 }
 
@@ -244,17 +1410,35 @@ T            - name node";
                         SL::Node(sn_k) => {
                             let sn_v = graph.nodes[sn_k].get_p_v();
                             held_sn_k_v = Some((sn_k, sn_v));
+                            held_left_node = false;
                         }
                         SL::Edge(_) => {}
                         SL::None => {
-                            let n_k = graph.add_node(Node::from(m_v));
-                            held_sn_k_v = Some((n_k, m_v));
+                            let n_k = graph.add_node(Node::from(gm_v));
+                            held_sn_k_v = Some((n_k, gm_v));
+                            held_left_node = false;
                         }
                     }
                 }
             }
+            // `selected` predates a node created by this press, so the press frame is skipped
+            if let Some((sn0_k, _)) = held_sn_k_v.filter(|_| !is_mouse_button_pressed(MouseButton::Left)) {
+                if !matches!(selected, SL::Node(sn_k) if sn_k == sn0_k) { held_left_node = true; }
+            }
+
             //LMB released
             if is_mouse_button_released(MouseButton::Left) {
+                if let Some(v) = region_rect_v.take() {
+                    region = Region::rect(v, gm_v);
+                    drawing_region = false;
+                }
+                if let Some(v) = rect_sel_v.take() {
+                    match v.distance(m_v) < NODE_RADIUS {
+                        true => selection.clear(),
+                        false => selection.extend(graph.find_nodes_in_rect(v, m_v))
+                    }
+                }
+
                 match selected {
                     SL::Node(sn_k) => {
                         let sn_v = graph.nodes[sn_k].get_p_v();
@@ -264,14 +1448,18 @@ T            - name node";
                                 graph.add_edge(Edge::from(sn0_v, sn_v), sn0_k, sn_k);
                                 if undirected { graph.add_edge(Edge::from(sn_v, sn0_v), sn_k, sn0_k); }
                             }
+                            // dragging out of a node and back onto it makes a self-loop
+                            else if held_left_node {
+                                graph.add_edge(Edge::from(sn_v, sn_v), sn_k, sn_k);
+                            }
                         }
                     }
                     SL::Edge(_) | SL::None => {
                         if let Some((sn0_k, sn0_v)) = held_sn_k_v {
-                            let dn_k = graph.add_node(Node::from(m_v));
+                            let dn_k = graph.add_node(Node::from(gm_v));
 
-                            graph.add_edge(Edge::from(sn0_v, m_v), sn0_k, dn_k);
-                            if undirected { graph.add_edge(Edge::from(m_v, sn0_v), dn_k, sn0_k); }
+                            graph.add_edge(Edge::from(sn0_v, gm_v), sn0_k, dn_k);
+                            if undirected { graph.add_edge(Edge::from(gm_v, sn0_v), dn_k, sn0_k); }
                         }
                     }
                 }
@@ -279,12 +1467,23 @@ T            - name node";
             }
 
 
+            //Shift + RMB
+            if is_key_down(KeyCode::LeftShift) && is_mouse_button_pressed(MouseButton::Right) {
+                if let Some((e_k, i)) = graph.edge_handle_at(&m_v) {
+                    graph.remove_edge_handle(e_k, i);
+                    held_handle = None;
+                    selected = SL::None;
+                }
+            }
+
             //RMB
-            if is_mouse_button_down(MouseButton::Right) {
+            if is_mouse_button_down(MouseButton::Right) && !drawing_region && !calibrating && !rmb_consumed && !is_key_down(KeyCode::LeftShift) {
                 match selected {
                     SL::Node(sn_k) => {
                         graph.remove_node(sn_k);
                         names.remove(sn_k);
+                        selection.retain_existing(&graph);
+                        force_layout.retain_existing(&graph);
                         selected = SL::None;
                     }
                     SL::Edge(se_k) => {
@@ -297,48 +1496,64 @@ T            - name node";
 
 
             //N
-            if is_key_down(KeyCode::N) && !graph.nodes.is_empty() {
-                let n_k = graph.nodes.keys().choose(&mut thread_rng()).unwrap();
-                let n_v = graph.nodes[n_k].get_p_v();
-
-
-                let rv = n_v + Vec2::new((random::<f32>() - 0.5) * 2.0 * max_neighbour_spawn_dist,
-                                         (random::<f32>() - 0.5) * 2.0 * max_neighbour_spawn_dist);
-                if graph.find_nodes(rv, 0.0, no_neighbour_spawn_dist).is_empty() &&
-                    !graph.find_nodes(rv, 0.0, max_neighbour_spawn_dist).is_empty() {
-                    graph.add_node(Node::from(rv));
-                }
+            if is_key_down(KeyCode::N) {
+                spawn_node(&mut graph, &mut rng, no_neighbour_spawn_dist, max_neighbour_spawn_dist);
             }
 
 
             //E
-            if is_key_down(KeyCode::E) && !graph.nodes.is_empty() {
-                let n_k = graph.nodes.keys().choose(&mut thread_rng()).unwrap();
-                let n_v = graph.nodes[n_k].get_p_v();
-
-                if let Some(&rn_k) = graph.find_nodes(n_v, min_edge_length, max_edge_length).choose() {
-                    let rn_v = graph.nodes[rn_k].get_p_v();
-
-                    graph.add_edge(Edge::from(n_v, rn_v), n_k, rn_k);
-                }
+            if is_key_down(KeyCode::E) {
+                spawn_edge(&mut graph, &mut rng, min_edge_length, max_edge_length);
             }
 
 
             if let Some(sn_k) = shift_held_sn_k {
-                graph.set_node_pos(sn_k, &m_v);
+                graph.set_node_pos(sn_k, &gm_v);
             }
-            if is_mouse_button_released(MouseButton::Left) || is_key_released(KeyCode::LeftShift) {
-                if shift_held_sn_k.is_some() {
-                    shift_held_sn_k = None;
+            if let Some((e_k, i)) = held_handle {
+                match graph.edges.contains_key(e_k) {
+                    true => graph.move_edge_handle(e_k, i, &gm_v),
+                    false => held_handle = None
                 }
             }
+            if is_mouse_button_released(MouseButton::Left) || is_key_released(KeyCode::LeftShift) {
+                shift_held_sn_k = None;
+                held_handle = None;
+            }
+        }
+
+
+        if algo_player.is_outdated(&graph, weight_attr.as_deref(), start_n_k, finish_n_k) {
+            algo_player.clear();
+        }
+        algo_player.update(get_frame_time());
+
+
+        if force_layout.running || step_layout {
+            if step_layout && force_layout.converged() { force_layout.restart(); }
+            force_layout.step(&mut graph, shift_held_sn_k);
+            if force_layout.converged() { force_layout.running = false; }
+            step_layout = false;
         }
 
 
         {
             let (_mwx, mwy) = mouse_wheel();
-            if mwy > 0.0 {cam.zoom *= 1.02}
-            else if mwy < 0.0 {cam.zoom /= 1.02}
+            let m_scr_v = Vec2::from(mouse_position());
+            if !mouse_over_ui {
+                if mwy > 0.0 {zoom_at(&mut cam, ZOOM_STEP, m_scr_v)}
+                else if mwy < 0.0 {zoom_at(&mut cam, 1.0 / ZOOM_STEP, m_scr_v)}
+
+                if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {zoom_at(&mut cam, KEY_ZOOM_STEP, m_scr_v)}
+                if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {zoom_at(&mut cam, 1.0 / KEY_ZOOM_STEP, m_scr_v)}
+                if is_key_pressed(KeyCode::Home) {
+                    let keys = graph.nodes.keys().collect::<Vec<NodeKey>>();
+                    fit_nodes(&mut cam, &graph, &keys);
+                }
+                if is_key_pressed(KeyCode::End) {
+                    fit_nodes(&mut cam, &graph, &selection.keys_or_all(&graph, true));
+                }
+            }
 
             let mdp = mouse_delta_position();
             if is_key_down(KeyCode::LeftControl) && is_mouse_button_down(MouseButton::Left) {
@@ -349,7 +1564,10 @@ T            - name node";
 
         if let Some(path) = load_map {
             match load_texture(path.as_str()).await {
-                Ok(texture) => map = Some(texture),
+                Ok(texture) => {
+                    map = Some(texture);
+                    graph.background.path = Some(path);
+                }
                 Err(e) => {
                     println!("{}", e);
                     map = None;
@@ -364,9 +1582,28 @@ T            - name node";
 
 
         if let Some(ref texture) = map {
-            draw_texture(*texture, 0.0, 0.0, WHITE);
+            graph.background.draw(texture);
+        }
+        if show_grid {
+            draw_grid(&cam, grid_spacing);
+        }
+        if calibrating || !calibration_points.is_empty() {
+            draw_calibration(&calibration_points, if calibrating { Some(m_v) } else { None });
+        }
+        match region_rect_v {
+            Some(v) => Region::rect(v, gm_v).draw(true, None),
+            None => region.draw(!drawing_region, if drawing_region { Some(gm_v) } else { None })
         }
 
+        selection.draw(&graph);
+        if let Some(v) = rect_sel_v {
+            draw_selection_rect(v, m_v);
+        }
+        for &n_k in &force_layout.pinned {
+            if let Some(n) = graph.nodes.get(n_k) {
+                n.draw(PINNED_NODE_RADIUS, PINNED_NODE_COLOR);
+            }
+        }
         if let Some(n_k) = start_n_k {
             match graph.nodes.get(n_k) {
                 Some(n) => n.draw(PF_NODE_RADIUS, PF_START_COLOR),
@@ -385,8 +1622,10 @@ T            - name node";
                     let sn = &graph.nodes[sn_k];
                     sn.draw(SELECTED_NODE_RADIUS, SELECTED_NODE_COLOR);
                     let sn_v = sn.get_p_v();
-                    if let Some((_, sn0_v)) = held_sn_k_v {
-                        Edge::from(sn0_v, sn_v).draw(EDGE_THICKNESS, DRAG_EDGE_COLOR);
+                    if let Some((sn0_k, sn0_v)) = held_sn_k_v {
+                        if sn0_k != sn_k || held_left_node {
+                            Edge::from(sn0_v, sn_v).draw(EDGE_THICKNESS, DRAG_EDGE_COLOR);
+                        }
                     }
                 }
 
@@ -394,29 +1633,47 @@ T            - name node";
                     let se = &graph.edges[se_k];
                     se.draw_selected(EDGE_THICKNESS, SELECTED_EDGE_THICKNESS, SELECTED_EDGE_COLOR);
                     if let Some((_, sn0_v)) = held_sn_k_v {
-                        Edge::from(sn0_v, m_v).draw(EDGE_THICKNESS, DRAG_EDGE_COLOR);
-                        Node::from(m_v).draw(NODE_RADIUS, DRAG_NODE_COLOR);
+                        Edge::from(sn0_v, gm_v).draw(EDGE_THICKNESS, DRAG_EDGE_COLOR);
+                        Node::from(gm_v).draw(NODE_RADIUS, DRAG_NODE_COLOR);
                     }
                 }
 
                 SL::None => {
                     if let Some((_, sn0_v)) = held_sn_k_v {
-                        Edge::from(sn0_v, m_v).draw(EDGE_THICKNESS, DRAG_EDGE_COLOR);
-                        Node::from(m_v).draw(NODE_RADIUS, DRAG_NODE_COLOR);
+                        Edge::from(sn0_v, gm_v).draw(EDGE_THICKNESS, DRAG_EDGE_COLOR);
+                        Node::from(gm_v).draw(NODE_RADIUS, DRAG_NODE_COLOR);
                     }
                 }
             }
         }
 
-        if let (Some(a), Some(b)) = (start_n_k, finish_n_k) {
-            let (n_path, e_path, _d) = find_shortest_path(&graph, a, b);
-            if n_path.len() > 1 {
+        algo_player.draw(&graph);
+        if let Some(t) = &traversal {
+            t.draw(&graph);
+        }
+        if let Some((n_path, e_path, _d)) = path {
+            if n_path.len() > 1 && !algo_player.is_active() {
                 graph.draw_path_gradient(n_path, e_path);
             }
         }
-        graph.draw_nodes();
-        graph.draw_edges();
+        let search_keys = match search_filter && !search_query.trim().is_empty() {
+            true => Some(search_nodes(&graph, &names, &search_query)
+                .into_iter()
+                .map(|hit| hit.key)
+                .collect::<HashSet<NodeKey>>()),
+            false => None
+        };
+        graph.refresh_styles();
+        graph.draw_nodes(search_keys.as_ref());
+        graph.draw_edges(search_keys.as_ref());
+        if let Some(l) = &levels {
+            l.draw_levels(&graph, search_keys.as_ref());
+        }
         if draw_lengths {graph.draw_lenghts();}
+        if is_key_down(KeyCode::LeftShift) {graph.draw_edge_handles();}
+
+        set_default_camera();
+        minimap.draw(&graph, map.as_ref().map(|t| (t, &graph.background)), &cam);
 
 
 
@@ -427,6 +1684,6 @@ T            - name node";
         next_frame().await;
     }
     let json_str = serde_json::to_string_pretty(&graph).unwrap();
-    fs::write(GRAPH_PATH, json_str.as_str()).expect(&*format!("error occurred while writing to {}", GRAPH_PATH));
+    fs::write(GRAPH_PATH, json_str.as_str()).unwrap_or_else(|_| panic!("error occurred while writing to {}", GRAPH_PATH));
     println!("end of program");
 }
\ No newline at end of file
@@ -0,0 +1,141 @@
+use macroquad::camera::Camera2D;
+use macroquad::color::*;
+use macroquad::math::Vec2;
+use macroquad::shapes::{draw_circle, draw_line, draw_rectangle, draw_rectangle_lines};
+use macroquad::texture::Texture2D;
+use macroquad::window::{screen_width, screen_height};
+use crate::{Graph, Background, EdgeGetSet, NodeGetSet};
+
+
+
+pub const MINIMAP_SIZE: f32 = 200.0;
+pub const MINIMAP_MARGIN: f32 = 10.0;
+pub const MINIMAP_PADDING: f32 = 6.0;
+pub const MINIMAP_NODE_RADIUS: f32 = 1.5;
+pub const MINIMAP_BACKGROUND_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+pub const MINIMAP_BORDER_COLOR: Color = GRAY;
+pub const MINIMAP_NODE_COLOR: Color = WHITE;
+pub const MINIMAP_EDGE_COLOR: Color = Color::new(0.7, 0.7, 0.7, 0.6);
+pub const MINIMAP_VIEW_COLOR: Color = YELLOW;
+
+
+// overview in the bottom right corner of the window, drawn in screen space
+pub struct Minimap {
+    pub visible: bool,
+    dragging: bool,
+    // world area shown, frozen while dragging so the map does not shift under the pointer
+    bounds: (Vec2, Vec2)
+}
+
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            dragging: false,
+            bounds: (Vec2::ZERO, Vec2::ONE)
+        }
+    }
+
+    fn screen_rect(&self) -> (Vec2, Vec2) {
+        let size = Vec2::splat(MINIMAP_SIZE);
+        let pos = Vec2::new(screen_width(), screen_height()) - size - Vec2::splat(MINIMAP_MARGIN);
+        (pos, size)
+    }
+
+    pub fn contains(&self, screen_v: Vec2) -> bool {
+        let (pos, size) = self.screen_rect();
+        self.visible && pos.x <= screen_v.x && screen_v.x <= pos.x + size.x && pos.y <= screen_v.y && screen_v.y <= pos.y + size.y
+    }
+
+    // origin and scale of the world to minimap transform, the content keeps its aspect ratio
+    fn transform(&self) -> (Vec2, f32) {
+        let (pos, size) = self.screen_rect();
+        let (min, max) = self.bounds;
+        let inner = size - Vec2::splat(2.0 * MINIMAP_PADDING);
+        let extent = (max - min).max(Vec2::ONE);
+        let scale = (inner.x / extent.x).min(inner.y / extent.y);
+        let origin = pos + Vec2::splat(MINIMAP_PADDING) + (inner - extent * scale) / 2.0 - min * scale;
+        (origin, scale)
+    }
+
+    fn to_screen(&self, v: Vec2) -> Vec2 {
+        let (origin, scale) = self.transform();
+        origin + v * scale
+    }
+
+    fn to_world(&self, screen_v: Vec2) -> Vec2 {
+        let (origin, scale) = self.transform();
+        (screen_v - origin) / scale
+    }
+
+    // `map` is the world rectangle covered by the background map
+    pub fn update_bounds(&mut self, graph: &Graph, map: Option<(Vec2, Vec2)>, cam: &Camera2D) {
+        if self.dragging { return; }
+
+        let view_min = cam.screen_to_world(Vec2::ZERO);
+        let view_max = cam.screen_to_world(Vec2::new(screen_width(), screen_height()));
+        let mut min = view_min.min(view_max);
+        let mut max = view_min.max(view_max);
+
+        for n in graph.nodes.values() {
+            min = min.min(n.get_p_v());
+            max = max.max(n.get_p_v());
+        }
+        if let Some((a, b)) = map {
+            min = min.min(a.min(b));
+            max = max.max(a.max(b));
+        }
+        self.bounds = (min, max);
+    }
+
+    // moves the camera while the minimap is pressed, returns true if the pointer belongs to it
+    pub fn handle_input(&mut self, cam: &mut Camera2D, pressed: bool, down: bool, screen_v: Vec2) -> bool {
+        if !self.visible {
+            self.dragging = false;
+            return false;
+        }
+        if pressed && self.contains(screen_v) { self.dragging = true; }
+        if !down { self.dragging = false; }
+
+        if self.dragging {
+            cam.target = self.to_world(screen_v);
+        }
+        self.dragging || self.contains(screen_v)
+    }
+
+    pub fn draw(&self, graph: &Graph, map: Option<(&Texture2D, &Background)>, cam: &Camera2D) {
+        if !self.visible { return; }
+        let (pos, size) = self.screen_rect();
+        let (_origin, scale) = self.transform();
+
+        draw_rectangle(pos.x, pos.y, size.x, size.y, MINIMAP_BACKGROUND_COLOR);
+
+        if let Some((texture, background)) = map {
+            background.draw_mapped(texture, |v| self.to_screen(v), scale);
+        }
+
+        for e in graph.edges.values().filter(|e| !e.is_loop()) {
+            let (a, b) = (self.to_screen(e.get_p1_v()), self.to_screen(e.get_p2_v()));
+            draw_line(a.x, a.y, b.x, b.y, 1.0, MINIMAP_EDGE_COLOR);
+        }
+        for n in graph.nodes.values() {
+            let v = self.to_screen(n.get_p_v());
+            draw_circle(v.x, v.y, MINIMAP_NODE_RADIUS, MINIMAP_NODE_COLOR);
+        }
+
+        let a = self.to_screen(cam.screen_to_world(Vec2::ZERO));
+        let b = self.to_screen(cam.screen_to_world(Vec2::new(screen_width(), screen_height())));
+        let (view_min, view_max) = (a.min(b).max(pos), a.max(b).min(pos + size));
+        if view_min.x < view_max.x && view_min.y < view_max.y {
+            draw_rectangle_lines(view_min.x, view_min.y, view_max.x - view_min.x, view_max.y - view_min.y, 2.0, MINIMAP_VIEW_COLOR);
+        }
+        draw_rectangle_lines(pos.x, pos.y, size.x, size.y, 2.0, MINIMAP_BORDER_COLOR);
+    }
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,12 +1,9 @@
-use crate::{NodePos, NodeKeys, NodeKey};
+use crate::{NodePos, NodeKeys, NodeKey, Attributes, NodeStyle, NodeShape, NODE_RADIUS, NODE_COLOR, NODE_OUTLINE_THICKNESS};
+use crate::{draw_node_shape, draw_node_outline, to_color};
 use serde::{Deserialize, Serialize};
 pub use std::collections::HashSet;
-use macroquad::{
-    color::*,
-    prelude::{
-        draw_circle,
-    }
-};
+pub use std::collections::BTreeMap;
+use macroquad::color::*;
 use macroquad::math::Vec2;
 
 
@@ -35,20 +32,17 @@ pub(crate) trait NodeGraph {
 
 
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct Node {
     pub(crate) pos: NodePos,
     pub(crate) keys: Option<NodeKeys>,
-
-}
-
-impl Default for Node {
-    fn default() -> Self {
-        Self {
-            pos: NodePos::default(),
-            keys: None
-        }
-    }
+    #[serde(default)]
+    pub(crate) attributes: Attributes,
+    #[serde(default, skip_serializing_if = "NodeStyle::is_empty")]
+    pub(crate) style: NodeStyle,
+    // own style on top of the matching graph rules, kept up to date by the graph
+    #[serde(skip)]
+    pub(crate) resolved: NodeStyle
 }
 
 impl From<(f32, f32)> for Node {
@@ -78,8 +72,42 @@ impl Node {
         }
     }
 
+    pub fn get_attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    pub fn get_style(&self) -> &NodeStyle {
+        &self.style
+    }
+
+    pub fn get_resolved_style(&self) -> &NodeStyle {
+        &self.resolved
+    }
+
+    pub fn get_radius(&self) -> f32 {
+        self.resolved.radius.unwrap_or(NODE_RADIUS)
+    }
+
+    // `r` is meant for a node of the default radius and is scaled with the styled one,
+    // the shape follows the style while the color is kept
     pub fn draw(&self, r: f32, col: Color) {
-        draw_circle(self.pos.x, self.pos.y, r, col);
+        let r = r * self.get_radius() / NODE_RADIUS;
+        let shape = self.resolved.shape.unwrap_or(NodeShape::Circle);
+        draw_node_shape(self.get_p_v(), shape, r, col);
+    }
+
+    // `alpha` scales the opacity, used to dim filtered out nodes
+    pub fn draw_styled(&self, alpha: f32) {
+        let s = &self.resolved;
+        let fade = |mut c: Color| { c.a *= alpha; c };
+        let v = self.get_p_v();
+        let r = self.get_radius();
+        let shape = s.shape.unwrap_or(NodeShape::Circle);
+
+        draw_node_shape(v, shape, r, fade(s.fill.map_or(NODE_COLOR, to_color)));
+        if let Some(outline) = s.outline {
+            draw_node_outline(v, shape, r, s.outline_thickness.unwrap_or(NODE_OUTLINE_THICKNESS), fade(to_color(outline)));
+        }
     }
 }
 
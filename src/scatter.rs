@@ -0,0 +1,267 @@
+use std::f32::consts::PI;
+use macroquad::color::*;
+use macroquad::math::Vec2;
+use macroquad::shapes::draw_line;
+use rand::Rng;
+use crate::{Graph, Node, NodeKey, NodeGetSet};
+
+
+
+pub const REGION_COLOR: Color = DARKGREEN;
+pub const REGION_THICKNESS: f32 = 2.0;
+const SAMPLE_ATTEMPTS: usize = 30;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum RegionShape {Rectangle, Polygon}
+
+
+pub struct Region {
+    pub points: Vec<Vec2>
+}
+
+
+impl Region {
+    pub fn new() -> Self {
+        Self {
+            points: Vec::new()
+        }
+    }
+
+    pub fn rect(v1: Vec2, v2: Vec2) -> Self {
+        let min = v1.min(v2);
+        let max = v1.max(v2);
+        Self {
+            points: vec![min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.points.len() > 2
+    }
+
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        let first = self.points.first().copied().unwrap_or(Vec2::ZERO);
+        let min = self.points.iter().fold(first, |acc, &v| acc.min(v));
+        let max = self.points.iter().fold(first, |acc, &v| acc.max(v));
+        (min, max)
+    }
+
+    // even-odd rule, works for concave polygons
+    pub fn contains(&self, v: Vec2) -> bool {
+        let n = self.points.len();
+        let mut inside = false;
+
+        for i in 0..n {
+            let a = self.points[i];
+            let b = self.points[(i + n - 1) % n];
+            if (a.y > v.y) != (b.y > v.y) && v.x < (b.x - a.x) * (v.y - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    pub fn draw(&self, closed: bool, cursor: Option<Vec2>) {
+        let mut points = self.points.clone();
+        if let Some(v) = cursor { points.push(v); }
+
+        for w in points.windows(2) {
+            draw_line(w[0].x, w[0].y, w[1].x, w[1].y, REGION_THICKNESS, REGION_COLOR);
+        }
+        if closed && points.len() > 2 {
+            let (a, b) = (points[points.len() - 1], points[0]);
+            draw_line(a.x, a.y, b.x, b.y, REGION_THICKNESS, REGION_COLOR);
+        }
+    }
+}
+
+impl Default for Region {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+
+struct SampleGrid {
+    origin: Vec2,
+    cell: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<Vec2>>
+}
+
+impl SampleGrid {
+    fn new(min: Vec2, max: Vec2, cell: f32) -> Self {
+        let cols = ((max.x - min.x) / cell).ceil() as usize + 1;
+        let rows = ((max.y - min.y) / cell).ceil() as usize + 1;
+        Self { origin: min, cell, cols, rows, cells: vec![Vec::new(); cols * rows] }
+    }
+
+    fn index(&self, v: Vec2) -> Option<(usize, usize)> {
+        let p = (v - self.origin) / self.cell;
+        match p.x >= 0.0 && p.y >= 0.0 && (p.x as usize) < self.cols && (p.y as usize) < self.rows {
+            true => Some((p.x as usize, p.y as usize)),
+            false => None
+        }
+    }
+
+    fn insert(&mut self, v: Vec2) {
+        if let Some((c, r)) = self.index(v) {
+            self.cells[r * self.cols + c].push(v);
+        }
+    }
+
+    fn is_free(&self, v: Vec2, r: f32) -> bool {
+        let Some((c, row)) = self.index(v) else { return false; };
+        let reach = (r / self.cell).ceil() as usize;
+
+        for y in row.saturating_sub(reach)..(row + reach + 1).min(self.rows) {
+            for x in c.saturating_sub(reach)..(c + reach + 1).min(self.cols) {
+                if self.cells[y * self.cols + x].iter().any(|&p| p.distance_squared(v) < r * r) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+
+// Bridson's poisson-disk sampling inside the region, no sample is closer than `r`
+// to another sample or to any of the `obstacles`
+pub fn poisson_disk<R: Rng>(region: &Region, r: f32, obstacles: &[Vec2], rng: &mut R) -> Vec<Vec2> {
+    if !region.is_valid() || r <= 0.0 { return Vec::new(); }
+
+    let (min, max) = region.bounds();
+    let mut grid = SampleGrid::new(min - Vec2::splat(r), max + Vec2::splat(r), r / 2f32.sqrt());
+    for &v in obstacles {
+        grid.insert(v);
+    }
+
+    let random_point = |rng: &mut R| Vec2::new(rng.gen_range(min.x..=max.x), rng.gen_range(min.y..=max.y));
+    let mut samples = Vec::new();
+    let mut active: Vec<Vec2> = obstacles.iter().copied().filter(|&v| region.contains(v)).collect();
+
+    loop {
+        if active.is_empty() {
+            // concave regions may have parts unreachable from earlier samples
+            let seed = (0..SAMPLE_ATTEMPTS)
+                .map(|_| random_point(rng))
+                .find(|&v| region.contains(v) && grid.is_free(v, r));
+            match seed {
+                Some(v) => {
+                    grid.insert(v);
+                    samples.push(v);
+                    active.push(v);
+                }
+                None => break
+            }
+        }
+
+        let i = rng.gen_range(0..active.len());
+        let base = active[i];
+        let found = (0..SAMPLE_ATTEMPTS)
+            .map(|_| {
+                let angle = rng.gen_range(0.0..2.0 * PI);
+                let dist = rng.gen_range(r..2.0 * r);
+                base + Vec2::new(angle.cos(), angle.sin()) * dist
+            })
+            .find(|&v| region.contains(v) && grid.is_free(v, r));
+
+        match found {
+            Some(v) => {
+                grid.insert(v);
+                samples.push(v);
+                active.push(v);
+            }
+            None => { active.swap_remove(i); }
+        }
+    }
+    samples
+}
+
+
+pub fn scatter_nodes<R: Rng>(graph: &mut Graph, region: &Region, min_dist: f32, rng: &mut R) -> Vec<NodeKey> {
+    let (min, max) = region.bounds();
+    let (min, max) = (min - Vec2::splat(min_dist), max + Vec2::splat(min_dist));
+    let obstacles = graph.nodes
+        .values()
+        .map(|n| n.get_p_v())
+        .filter(|v| min.x <= v.x && v.x <= max.x && min.y <= v.y && v.y <= max.y)
+        .collect::<Vec<Vec2>>();
+
+    poisson_disk(region, min_dist, &obstacles, rng)
+        .into_iter()
+        .map(|v| graph.add_node(Node::from(v)))
+        .collect::<Vec<NodeKey>>()
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::testing::*;
+    use super::*;
+
+    fn min_spacing(points: &[Vec2]) -> f32 {
+        let mut min = f32::INFINITY;
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i + 1..] {
+                min = min.min(a.distance(*b));
+            }
+        }
+        min
+    }
+
+    #[test]
+    fn samples_keep_their_distance() {
+        let region = Region::rect(Vec2::ZERO, Vec2::splat(200.0));
+        let samples = poisson_disk(&region, 20.0, &[], &mut StdRng::seed_from_u64(3));
+
+        // disks of radius 10 around the samples are disjoint and lie in a 220 x 220 square
+        assert!(samples.len() > 30 && samples.len() as f32 * PI * 100.0 <= 220.0 * 220.0);
+        assert!(min_spacing(&samples) >= 20.0);
+        assert!(samples.iter().all(|&v| region.contains(v)));
+    }
+
+    #[test]
+    fn samples_avoid_obstacles() {
+        let region = Region::rect(Vec2::ZERO, Vec2::splat(100.0));
+        let obstacles = [Vec2::splat(50.0), Vec2::new(0.0, 0.0)];
+        let samples = poisson_disk(&region, 15.0, &obstacles, &mut StdRng::seed_from_u64(4));
+
+        assert!(!samples.is_empty());
+        assert!(samples.iter().all(|v| obstacles.iter().all(|o| v.distance(*o) >= 15.0)));
+    }
+
+    #[test]
+    fn concave_region() {
+        // an L shape, the top right quarter is outside
+        let region = Region {
+            points: vec![Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(100.0, 50.0),
+                         Vec2::new(50.0, 50.0), Vec2::new(50.0, 100.0), Vec2::new(0.0, 100.0)]
+        };
+        assert!(region.contains(Vec2::new(25.0, 75.0)));
+        assert!(!region.contains(Vec2::new(75.0, 75.0)));
+
+        let samples = poisson_disk(&region, 10.0, &[], &mut StdRng::seed_from_u64(5));
+        assert!(samples.iter().all(|&v| region.contains(v)));
+        assert!(min_spacing(&samples) >= 10.0);
+    }
+
+    #[test]
+    fn scattered_nodes_avoid_existing_ones() {
+        let (mut graph, n, _e) = path_graph(3);
+        let region = Region::rect(Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+        let added = scatter_nodes(&mut graph, &region, 12.0, &mut StdRng::seed_from_u64(6));
+
+        assert!(!added.is_empty());
+        assert_eq!(graph.nodes.len(), added.len() + 3);
+        let vs = n.iter().map(|&k| graph.nodes[k].get_p_v()).collect::<Vec<Vec2>>();
+        assert!(added.iter().all(|&k| vs.iter().all(|v| graph.nodes[k].get_p_v().distance(*v) >= 12.0)));
+    }
+}
@@ -0,0 +1,120 @@
+use slotmap::SecondaryMap;
+use crate::{Graph, NodeKey};
+
+
+
+pub const DIM_ALPHA: f32 = 0.15;
+pub const SEARCH_MAX_HITS: usize = 100;
+
+
+pub struct SearchHit {
+    pub key: NodeKey,
+    // what matched, the name or "attribute: value"
+    pub text: String
+}
+
+
+// case-insensitive substring search over names and attributes,
+// "attr=value" only looks at the values of the given attribute.
+// name hits come first
+pub fn search_nodes(graph: &Graph, names: &SecondaryMap<NodeKey, Box<String>>, query: &str) -> Vec<SearchHit> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() { return Vec::new(); }
+
+    let (attr, value) = match query.split_once('=') {
+        Some((a, v)) => (Some(a.trim().to_string()), v.trim().to_string()),
+        None => (None, query.clone())
+    };
+
+    let mut by_name = Vec::new();
+    let mut by_attr = Vec::new();
+
+    for (n_k, n) in &graph.nodes {
+        if attr.is_none() {
+            if let Some(name) = names.get(n_k) {
+                if name.to_lowercase().contains(&value) {
+                    by_name.push(SearchHit { key: n_k, text: name.to_string() });
+                    continue;
+                }
+            }
+        }
+
+        // attributes set on the node and the schema defaults it does not override
+        let own = n.get_attributes();
+        let hit = own
+            .keys()
+            .chain(graph.schema.node.keys().filter(|k| !own.contains_key(*k)))
+            .filter(|k| match &attr {
+                Some(a) => k.to_lowercase() == *a,
+                None => true
+            })
+            .filter_map(|k| graph.node_attr(n_k, k).map(|v| (k, v.to_string())))
+            .find(|(k, v)| v.to_lowercase().contains(&value) || (attr.is_none() && k.to_lowercase().contains(&value)));
+
+        if let Some((k, v)) = hit {
+            let text = match names.get(n_k) {
+                Some(name) => format!("{} ({}: {})", name, k, v),
+                None => format!("{}: {}", k, v)
+            };
+            by_attr.push(SearchHit { key: n_k, text });
+        }
+    }
+
+    by_name.sort_by(|a, b| a.text.cmp(&b.text));
+    by_attr.sort_by(|a, b| a.text.cmp(&b.text));
+    by_name.extend(by_attr);
+    by_name
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{AttrValue, AttrTarget};
+    use crate::testing::*;
+    use super::*;
+
+    // three nodes, the first two named "Alpha" and "Paris depot", the last in the city "Paris"
+    fn graph() -> (Graph, Vec<NodeKey>) {
+        let (mut graph, keys, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)], &[], false);
+        graph.set_node_attr(keys[2], "city", AttrValue::Text("Paris".to_string()));
+        (graph, keys)
+    }
+
+    // keys of the nodes found with the names of `graph()`
+    fn search(graph: &Graph, keys: &[NodeKey], query: &str) -> Vec<NodeKey> {
+        let mut names = SecondaryMap::new();
+        names.insert(keys[0], Box::new("Alpha".to_string()));
+        names.insert(keys[1], Box::new("Paris depot".to_string()));
+        search_nodes(graph, &names, query).iter().map(|hit| hit.key).collect()
+    }
+
+    #[test]
+    fn names_and_attributes() {
+        let (graph, keys) = graph();
+
+        assert!(search(&graph, &keys, "  ").is_empty());
+        assert_eq!(search(&graph, &keys, "ALPH"), vec![keys[0]]);
+        // name hits come first
+        assert_eq!(search(&graph, &keys, "paris"), vec![keys[1], keys[2]]);
+        // attribute names match too
+        assert_eq!(search(&graph, &keys, "cit"), vec![keys[2]]);
+    }
+
+    #[test]
+    fn attribute_query() {
+        let (graph, keys) = graph();
+
+        assert_eq!(search(&graph, &keys, "city = par"), vec![keys[2]]);
+        assert!(search(&graph, &keys, "city=alpha").is_empty());
+        assert!(search(&graph, &keys, "town=paris").is_empty());
+    }
+
+    #[test]
+    fn schema_defaults() {
+        let (mut graph, keys) = graph();
+        graph.define_attr(AttrTarget::Node, "kind", AttrValue::Text("stop".to_string()));
+
+        assert_eq!(search(&graph, &keys, "kind=stop").len(), 3);
+    }
+}
@@ -0,0 +1,119 @@
+use macroquad::color::*;
+use macroquad::math::Vec2;
+use macroquad::shapes::draw_rectangle_lines;
+use crate::{Graph, NodeKey, HashSet};
+
+
+
+pub const SELECTION_NODE_RADIUS: f32 = 8.0;
+pub const SELECTION_NODE_COLOR: Color = ORANGE;
+pub const SELECTION_RECT_COLOR: Color = ORANGE;
+
+
+#[derive(Default)]
+pub struct Selection {
+    pub nodes: HashSet<NodeKey>,
+    pub last: Option<NodeKey>
+}
+
+
+impl Selection {
+    pub fn new() -> Self {
+        Self {
+            nodes: HashSet::new(),
+            last: None
+        }
+    }
+
+    pub fn toggle(&mut self, key: NodeKey) {
+        if self.nodes.remove(&key) {
+            if self.last == Some(key) { self.last = None; }
+        } else {
+            self.nodes.insert(key);
+            self.last = Some(key);
+        }
+    }
+
+    pub fn extend(&mut self, keys: Vec<NodeKey>) {
+        self.nodes.extend(keys);
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.last = None;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn retain_existing(&mut self, graph: &Graph) {
+        self.nodes.retain(|&k| graph.nodes.contains_key(k));
+        if let Some(k) = self.last {
+            if !graph.nodes.contains_key(k) { self.last = None; }
+        }
+    }
+
+    pub fn root(&self) -> Option<NodeKey> {
+        self.last.or_else(|| self.nodes.iter().next().copied())
+    }
+
+    // selected nodes, or the whole graph when nothing is selected
+    pub fn keys_or_all(&self, graph: &Graph, selection_only: bool) -> Vec<NodeKey> {
+        match selection_only && !self.is_empty() {
+            true => graph.nodes.keys().filter(|k| self.nodes.contains(k)).collect(),
+            false => graph.nodes.keys().collect()
+        }
+    }
+
+    pub fn draw(&self, graph: &Graph) {
+        for &n_k in &self.nodes {
+            if let Some(n) = graph.nodes.get(n_k) {
+                n.draw(SELECTION_NODE_RADIUS, SELECTION_NODE_COLOR);
+            }
+        }
+    }
+}
+
+
+pub fn draw_selection_rect(v1: Vec2, v2: Vec2) {
+    let min = v1.min(v2);
+    let size = (v2 - v1).abs();
+    draw_rectangle_lines(min.x, min.y, size.x, size.y, 2.0, SELECTION_RECT_COLOR);
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use super::*;
+
+    #[test]
+    fn toggle_and_retain() {
+        let (mut graph, n, _e) = path_graph(3);
+        let mut selection = Selection::new();
+        selection.toggle(n[0]);
+        selection.toggle(n[1]);
+        assert_eq!(selection.root(), Some(n[1]));
+
+        graph.remove_node(n[1]);
+        selection.retain_existing(&graph);
+        assert_eq!(selection.nodes, HashSet::from([n[0]]));
+        assert_eq!(selection.root(), Some(n[0]));
+
+        assert_eq!(selection.keys_or_all(&graph, true), vec![n[0]]);
+        assert_eq!(selection.keys_or_all(&graph, false).len(), 2);
+        selection.toggle(n[0]);
+        assert_eq!(selection.keys_or_all(&graph, true).len(), 2);
+    }
+
+    #[test]
+    fn nodes_in_rect() {
+        let (graph, n, _e) = path_graph(4);
+        let mut found = graph.find_nodes_in_rect(Vec2::new(25.0, 5.0), Vec2::new(5.0, -5.0));
+        found.sort();
+        assert_eq!(found, vec![n[1], n[2]]);
+    }
+}
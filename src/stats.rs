@@ -0,0 +1,254 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use slotmap::SecondaryMap;
+use crate::{Graph, NodeKey, EdgeGetSet};
+
+
+
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    pub density: f32,
+    pub avg_degree: f32,
+    pub min_degree: usize,
+    pub max_degree: usize,
+    // every node reaches every other one, diameter and radius are infinite otherwise
+    pub connected: bool,
+    pub diameter: Option<f32>,
+    pub radius: Option<f32>,
+    pub avg_path_length: Option<f32>,
+    pub clustering: f32,
+    pub total_length: f32,
+    pub weight: Option<String>,
+    revision: u64
+}
+
+
+impl GraphStats {
+    // path based values use the numeric edge attribute `weight` when given
+    pub fn new(graph: &Graph, weight: Option<&str>) -> Self {
+        let n = graph.nodes.len();
+        let m = graph.edges.len();
+
+        let degrees = graph.nodes
+            .keys()
+            .map(|n_k| graph.degree(n_k))
+            .collect::<Vec<usize>>();
+
+        let density = match n > 1 {
+            true => m as f32 / (n * (n - 1)) as f32,
+            false => 0.0
+        };
+
+        let mut connected = true;
+        let mut diameter: Option<f32> = None;
+        let mut radius: Option<f32> = None;
+        let mut path_sum = 0.0;
+        let mut path_count = 0;
+
+        for n_k in graph.nodes.keys() {
+            let dists = shortest_distances(graph, n_k, weight);
+            let eccentricity = dists
+                .iter()
+                .filter(|(k, _d)| *k != n_k)
+                .map(|(_k, &d)| d)
+                .fold(None, |mx: Option<f32>, d| Some(mx.map_or(d, |mx| mx.max(d))));
+
+            match (eccentricity, dists.len() == n) {
+                (Some(ecc), true) => {
+                    diameter = Some(diameter.map_or(ecc, |d| d.max(ecc)));
+                    radius = Some(radius.map_or(ecc, |r| r.min(ecc)));
+                }
+                (_, false) => connected = false,
+                _ => {}
+            }
+
+            for (k, &d) in &dists {
+                if k != n_k {
+                    path_sum += d;
+                    path_count += 1;
+                }
+            }
+        }
+
+        Self {
+            nodes: n,
+            edges: m,
+            density,
+            avg_degree: match n {
+                0 => 0.0,
+                _ => degrees.iter().sum::<usize>() as f32 / n as f32
+            },
+            min_degree: degrees.iter().copied().min().unwrap_or(0),
+            max_degree: degrees.iter().copied().max().unwrap_or(0),
+            connected,
+            diameter: diameter.filter(|_| connected),
+            radius: radius.filter(|_| connected),
+            avg_path_length: match path_count {
+                0 => None,
+                _ => Some(path_sum / path_count as f32)
+            },
+            clustering: clustering_coefficient(graph),
+            total_length: total_edge_length(graph),
+            weight: weight.map(|w| w.to_string()),
+            revision: graph.revision()
+        }
+    }
+
+    pub fn is_outdated(&self, graph: &Graph, weight: Option<&str>) -> bool {
+        self.revision != graph.revision() || self.weight.as_deref() != weight
+    }
+}
+
+
+
+#[derive(PartialEq)]
+struct HeapItem(f32, NodeKey);
+
+impl Eq for HeapItem {}
+
+impl PartialOrd for HeapItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+
+pub fn shortest_distances(graph: &Graph, s_k: NodeKey, weight: Option<&str>) -> SecondaryMap<NodeKey, f32> {
+    let mut dists: SecondaryMap<NodeKey, f32> = SecondaryMap::new();
+    let mut heap = BinaryHeap::new();
+
+    dists.insert(s_k, 0.0);
+    heap.push(HeapItem(0.0, s_k));
+
+    while let Some(HeapItem(d, n_k)) = heap.pop() {
+        if d > dists[n_k] { continue; }
+
+        for (nn_k, e_k) in graph.successors(n_k) {
+            let nd = d + graph.edge_weight(e_k, weight).max(0.0);
+            let shorter = match dists.get(nn_k) {
+                Some(&od) => nd < od,
+                None => true
+            };
+            if shorter {
+                dists.insert(nn_k, nd);
+                heap.push(HeapItem(nd, nn_k));
+            }
+        }
+    }
+    dists
+}
+
+
+pub fn clustering_coefficient(graph: &Graph) -> f32 {
+    if graph.nodes.is_empty() { return 0.0; }
+
+    let neighbours: SecondaryMap<NodeKey, _> = graph.nodes
+        .keys()
+        .map(|n_k| (n_k, graph.neighbours(n_k)))
+        .collect();
+
+    let sum: f32 = neighbours
+        .values()
+        .map(|nbs| {
+            let k = nbs.len();
+            if k < 2 { return 0.0; }
+
+            let links = nbs
+                .iter()
+                .map(|&a| neighbours[a].intersection(nbs).count())
+                .sum::<usize>() / 2;
+
+            links as f32 / (k * (k - 1) / 2) as f32
+        })
+        .sum();
+
+    sum / graph.nodes.len() as f32
+}
+
+
+pub fn total_edge_length(graph: &Graph) -> f32 {
+    graph.edges.values().map(|e| e.get_length()).sum()
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use macroquad::math::Vec2;
+    use crate::{Node, AttrValue};
+    use crate::testing::*;
+    use super::*;
+
+    #[test]
+    fn path_distances() {
+        let (graph, _n, _e) = path_graph(4);
+        let s = GraphStats::new(&graph, None);
+
+        assert!(s.connected);
+        assert_eq!(s.diameter, Some(30.0));
+        assert_eq!(s.radius, Some(20.0));
+        assert!((s.avg_path_length.unwrap() - 100.0 / 6.0).abs() < 1e-4);
+        assert_eq!((s.min_degree, s.max_degree), (1, 2));
+        assert_eq!(s.total_length, 60.0);
+    }
+
+    #[test]
+    fn weighted_distances() {
+        let (mut graph, _n, _e) = path_graph(4);
+        let edges = graph.edges.keys().collect::<Vec<_>>();
+        for e_k in edges {
+            graph.set_edge_attr(e_k, "cost", AttrValue::Number(1.0));
+        }
+        let s = GraphStats::new(&graph, Some("cost"));
+
+        assert_eq!(s.diameter, Some(3.0));
+        assert_eq!(s.radius, Some(2.0));
+    }
+
+    #[test]
+    fn directed_distances() {
+        // a -> b -> c, only a reaches everything
+        let (graph, n, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (30.0, 0.0)], &[(0, 1), (1, 2)], false);
+        let dists = shortest_distances(&graph, n[0], None);
+        assert_eq!((dists[n[1]], dists[n[2]]), (10.0, 30.0));
+        assert!(!shortest_distances(&graph, n[2], None).contains_key(n[0]));
+
+        let s = GraphStats::new(&graph, None);
+        assert!(!s.connected);
+        assert_eq!(s.density, 2.0 / 6.0);
+    }
+
+    #[test]
+    fn disconnected_has_no_diameter() {
+        let (mut graph, _n, _e) = path_graph(3);
+        graph.add_node(Node::from(Vec2::new(100.0, 100.0)));
+        let s = GraphStats::new(&graph, None);
+
+        assert!(!s.connected);
+        assert_eq!(s.diameter, None);
+        assert_eq!(s.radius, None);
+        // only pairs within the path count
+        assert!((s.avg_path_length.unwrap() - 40.0 / 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn clustering() {
+        let (graph, _n, _e) = path_graph(3);
+        assert_eq!(clustering_coefficient(&graph), 0.0);
+
+        let (graph, _n, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)], &[(0, 1), (1, 2), (0, 2)], true);
+        assert_eq!(clustering_coefficient(&graph), 1.0);
+
+        // a pendant node lowers the local coefficient of its neighbour to 1/3
+        let (graph, _n, _e) = test_graph(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0), (-10.0, 0.0)], &[(0, 1), (1, 2), (0, 2), (0, 3)], true);
+        assert!((clustering_coefficient(&graph) - (1.0 / 3.0 + 1.0 + 1.0) / 4.0).abs() < 1e-6);
+    }
+}
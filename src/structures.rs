@@ -0,0 +1,224 @@
+use std::f32::consts::PI;
+use macroquad::math::Vec2;
+use crate::{Graph, Node, NodeKey, connect};
+
+
+
+pub const STRUCTURE_SIZE: usize = 5;
+pub const STRUCTURE_SPACING: f32 = 50.0;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Structure {
+    SquareLattice,
+    HexagonalLattice,
+    TriangularLattice,
+    Complete,
+    Cycle,
+    Star,
+    Wheel,
+    BinaryTree
+}
+
+impl Structure {
+    pub const ALL: [Structure; 8] = [
+        Structure::SquareLattice,
+        Structure::HexagonalLattice,
+        Structure::TriangularLattice,
+        Structure::Complete,
+        Structure::Cycle,
+        Structure::Star,
+        Structure::Wheel,
+        Structure::BinaryTree
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Structure::SquareLattice => "square lattice",
+            Structure::HexagonalLattice => "hexagonal lattice",
+            Structure::TriangularLattice => "triangular lattice",
+            Structure::Complete => "complete",
+            Structure::Cycle => "cycle",
+            Structure::Star => "star",
+            Structure::Wheel => "wheel",
+            Structure::BinaryTree => "binary tree"
+        }
+    }
+
+    pub fn size_label(&self) -> &'static str {
+        match self {
+            Structure::SquareLattice | Structure::HexagonalLattice | Structure::TriangularLattice => "side",
+            Structure::BinaryTree => "depth",
+            _ => "nodes"
+        }
+    }
+}
+
+
+
+fn ring(n: usize, spacing: f32) -> Vec<Vec2> {
+    let r = (spacing * n as f32 / (2.0 * PI)).max(spacing);
+    (0..n)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f32 / n as f32 - PI / 2.0;
+            Vec2::new(angle.cos(), angle.sin()) * r
+        })
+        .collect()
+}
+
+
+fn lattice(structure: Structure, side: usize, s: f32) -> (Vec<Vec2>, Vec<(usize, usize)>) {
+    let id = |r: usize, c: usize| r * side + c;
+    let mut points = Vec::new();
+    let mut pairs = Vec::new();
+
+    for r in 0..side {
+        for c in 0..side {
+            let (r_f, c_f) = (r as f32, c as f32);
+            points.push(match structure {
+                Structure::HexagonalLattice => {
+                    let lift = if (r + c) % 2 == 0 { 0.5 * s } else { 0.0 };
+                    Vec2::new(c_f * s * 3f32.sqrt() / 2.0, r_f * 1.5 * s + lift)
+                }
+                Structure::TriangularLattice => Vec2::new((c_f + 0.5 * (r % 2) as f32) * s, r_f * s * 3f32.sqrt() / 2.0),
+                _ => Vec2::new(c_f, r_f) * s
+            });
+
+            if c + 1 < side { pairs.push((id(r, c), id(r, c + 1))); }
+            if r + 1 == side { continue; }
+
+            match structure {
+                Structure::HexagonalLattice => {
+                    if (r + c) % 2 == 0 { pairs.push((id(r, c), id(r + 1, c))); }
+                }
+                Structure::TriangularLattice => {
+                    pairs.push((id(r, c), id(r + 1, c)));
+                    match r % 2 {
+                        0 => if c > 0 { pairs.push((id(r, c), id(r + 1, c - 1))); }
+                        _ => if c + 1 < side { pairs.push((id(r, c), id(r + 1, c + 1))); }
+                    }
+                }
+                _ => pairs.push((id(r, c), id(r + 1, c)))
+            }
+        }
+    }
+    (points, pairs)
+}
+
+
+fn binary_tree(depth: usize, s: f32) -> (Vec<Vec2>, Vec<(usize, usize)>) {
+    let width = 2f32.powi(depth as i32 - 1) * s;
+    let mut points = Vec::new();
+    let mut pairs = Vec::new();
+
+    for level in 0..depth {
+        let count = 1 << level;
+        for j in 0..count {
+            let i = points.len();
+            points.push(Vec2::new((j as f32 + 0.5) * width / count as f32, level as f32 * s));
+            if i > 0 { pairs.push(((i - 1) / 2, i)); }
+        }
+    }
+    (points, pairs)
+}
+
+
+// node positions relative to an arbitrary origin and (from, to) index pairs
+pub fn structure_shape(structure: Structure, size: usize, spacing: f32) -> (Vec<Vec2>, Vec<(usize, usize)>) {
+    let n = size;
+    match structure {
+        Structure::SquareLattice | Structure::HexagonalLattice | Structure::TriangularLattice => lattice(structure, size, spacing),
+        Structure::Complete => {
+            let pairs = (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b))).collect();
+            (ring(n, spacing), pairs)
+        }
+        Structure::Cycle => {
+            let pairs = match n {
+                0 | 1 => Vec::new(),
+                2 => vec![(0, 1)],
+                _ => (0..n).map(|i| (i, (i + 1) % n)).collect()
+            };
+            (ring(n, spacing), pairs)
+        }
+        Structure::Star | Structure::Wheel => {
+            let rim = n.saturating_sub(1);
+            let mut points = vec![Vec2::ZERO];
+            points.extend(ring(rim, spacing));
+
+            let mut pairs = (1..=rim).map(|i| (0, i)).collect::<Vec<(usize, usize)>>();
+            if structure == Structure::Wheel && rim > 2 {
+                pairs.extend((0..rim).map(|i| (i + 1, (i + 1) % rim + 1)));
+            }
+            (points, pairs)
+        }
+        Structure::BinaryTree => binary_tree(size, spacing)
+    }
+}
+
+
+// the bounding box of the structure is centered on `center`
+pub fn place_structure(graph: &mut Graph, structure: Structure, size: usize, spacing: f32, center: Vec2, undirected: bool) -> Vec<NodeKey> {
+    let (points, pairs) = structure_shape(structure, size, spacing);
+    if points.is_empty() { return Vec::new(); }
+
+    let min = points.iter().fold(points[0], |acc, &v| acc.min(v));
+    let max = points.iter().fold(points[0], |acc, &v| acc.max(v));
+    let offset = center - (min + max) / 2.0;
+
+    let keys = points
+        .iter()
+        .map(|&v| graph.add_node(Node::from(v + offset)))
+        .collect::<Vec<NodeKey>>();
+    for (a, b) in pairs {
+        connect(graph, keys[a], keys[b], undirected);
+    }
+    keys
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{HashSet, NodeGetSet};
+    use super::*;
+
+    #[test]
+    fn node_and_edge_counts() {
+        let counts = |structure: Structure, size: usize| {
+            let (points, pairs) = structure_shape(structure, size, STRUCTURE_SPACING);
+            (points.len(), pairs.len())
+        };
+        assert_eq!(counts(Structure::SquareLattice, 3), (9, 12));
+        assert_eq!(counts(Structure::Complete, 5), (5, 10));
+        assert_eq!(counts(Structure::Cycle, 5), (5, 5));
+        assert_eq!(counts(Structure::Cycle, 2), (2, 1));
+        assert_eq!(counts(Structure::Star, 5), (5, 4));
+        assert_eq!(counts(Structure::Wheel, 5), (5, 8));
+
+        let (points, pairs) = structure_shape(Structure::BinaryTree, 3, STRUCTURE_SPACING);
+        assert_eq!(pairs.len(), points.len() - 1);
+    }
+
+    #[test]
+    fn pairs_are_valid() {
+        for structure in Structure::ALL {
+            let (points, pairs) = structure_shape(structure, STRUCTURE_SIZE, STRUCTURE_SPACING);
+            let unique = pairs.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect::<HashSet<(usize, usize)>>();
+
+            assert_eq!(unique.len(), pairs.len(), "{}", structure.name());
+            assert!(pairs.iter().all(|&(a, b)| a != b && a < points.len() && b < points.len()), "{}", structure.name());
+        }
+    }
+
+    #[test]
+    fn placed_around_the_center() {
+        let mut graph = Graph::new();
+        let center = Vec2::new(100.0, -50.0);
+        let keys = place_structure(&mut graph, Structure::SquareLattice, 3, 10.0, center, true);
+
+        assert_eq!(keys.len(), 9);
+        assert_eq!(graph.edges.len(), 24);
+        let sum = keys.iter().fold(Vec2::ZERO, |acc, &k| acc + graph.nodes[k].get_p_v());
+        assert!((sum / 9.0).distance(center) < 1e-3);
+    }
+}
@@ -0,0 +1,217 @@
+use macroquad::color::Color;
+use macroquad::math::Vec2;
+use macroquad::shapes::{draw_circle, draw_circle_lines, draw_line, draw_rectangle, draw_rectangle_lines, draw_triangle};
+use serde::{Serialize, Deserialize};
+use crate::{AttrTarget, AttrValue};
+
+
+
+pub const NODE_OUTLINE_THICKNESS: f32 = 2.0;
+pub const DASH_LENGTH: f32 = 8.0;
+pub const DASH_GAP: f32 = 5.0;
+
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum NodeShape {Circle, Square, Diamond}
+
+impl NodeShape {
+    pub const ALL: [NodeShape; 3] = [NodeShape::Circle, NodeShape::Square, NodeShape::Diamond];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeShape::Circle => "circle",
+            NodeShape::Square => "square",
+            NodeShape::Diamond => "diamond"
+        }
+    }
+}
+
+
+// every field is an override, None keeps the value from the rules or the defaults
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub struct NodeStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill: Option<[f32; 4]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outline: Option<[f32; 4]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outline_thickness: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub radius: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<NodeShape>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub struct EdgeStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<[f32; 4]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thickness: Option<f32>,
+    // dash and gap length
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dash: Option<[f32; 2]>
+}
+
+
+impl NodeStyle {
+    // fields set in `other` win
+    pub fn with(&self, other: &NodeStyle) -> NodeStyle {
+        NodeStyle {
+            fill: other.fill.or(self.fill),
+            outline: other.outline.or(self.outline),
+            outline_thickness: other.outline_thickness.or(self.outline_thickness),
+            radius: other.radius.or(self.radius),
+            shape: other.shape.or(self.shape)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == NodeStyle::default()
+    }
+}
+
+impl EdgeStyle {
+    pub fn with(&self, other: &EdgeStyle) -> EdgeStyle {
+        EdgeStyle {
+            color: other.color.or(self.color),
+            thickness: other.thickness.or(self.thickness),
+            dash: other.dash.or(self.dash)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == EdgeStyle::default()
+    }
+}
+
+
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum RuleOp {Exists, Equals, Less, Greater}
+
+impl RuleOp {
+    pub const ALL: [RuleOp; 4] = [RuleOp::Exists, RuleOp::Equals, RuleOp::Less, RuleOp::Greater];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleOp::Exists => "exists",
+            RuleOp::Equals => "=",
+            RuleOp::Less => "<",
+            RuleOp::Greater => ">"
+        }
+    }
+}
+
+
+// applies `node` or `edge` (depending on `target`) to the elements whose attribute passes the test
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct StyleRule {
+    pub target: AttrTarget,
+    pub attr: String,
+    pub op: RuleOp,
+    pub value: AttrValue,
+    #[serde(default)]
+    pub node: NodeStyle,
+    #[serde(default)]
+    pub edge: EdgeStyle
+}
+
+impl StyleRule {
+    pub fn new(target: AttrTarget, attr: &str) -> Self {
+        Self {
+            target,
+            attr: attr.to_string(),
+            op: RuleOp::Exists,
+            value: AttrValue::Number(0.0),
+            node: NodeStyle::default(),
+            edge: EdgeStyle::default()
+        }
+    }
+
+    pub fn matches(&self, value: Option<&AttrValue>) -> bool {
+        let Some(value) = value else { return false; };
+        match self.op {
+            RuleOp::Exists => true,
+            RuleOp::Equals => match (value.as_number(), self.value.as_number()) {
+                (Some(a), Some(b)) => a == b,
+                _ => *value == self.value
+            },
+            RuleOp::Less | RuleOp::Greater => match (value.as_number(), self.value.as_number()) {
+                (Some(a), Some(b)) => if self.op == RuleOp::Less { a < b } else { a > b },
+                _ => false
+            }
+        }
+    }
+}
+
+
+
+pub fn to_color(c: [f32; 4]) -> Color {
+    Color::new(c[0], c[1], c[2], c[3])
+}
+
+
+pub fn draw_node_shape(v: Vec2, shape: NodeShape, r: f32, col: Color) {
+    match shape {
+        NodeShape::Circle => draw_circle(v.x, v.y, r, col),
+        NodeShape::Square => draw_rectangle(v.x - r, v.y - r, 2.0 * r, 2.0 * r, col),
+        NodeShape::Diamond => {
+            let (dx, dy) = (Vec2::new(r, 0.0), Vec2::new(0.0, r));
+            draw_triangle(v - dy, v + dx, v + dy, col);
+            draw_triangle(v - dy, v - dx, v + dy, col);
+        }
+    }
+}
+
+
+pub fn draw_node_outline(v: Vec2, shape: NodeShape, r: f32, t: f32, col: Color) {
+    match shape {
+        NodeShape::Circle => draw_circle_lines(v.x, v.y, r, t, col),
+        NodeShape::Square => draw_rectangle_lines(v.x - r, v.y - r, 2.0 * r, 2.0 * r, t * 2.0, col),
+        NodeShape::Diamond => {
+            let points = [v - Vec2::new(0.0, r), v + Vec2::new(r, 0.0), v + Vec2::new(0.0, r), v - Vec2::new(r, 0.0)];
+            for i in 0..4 {
+                let (a, b) = (points[i], points[(i + 1) % 4]);
+                draw_line(a.x, a.y, b.x, b.y, t, col);
+            }
+        }
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_tests() {
+        let mut rule = StyleRule::new(AttrTarget::Node, "size");
+        assert!(rule.matches(Some(&AttrValue::Text("a".to_string()))));
+        assert!(!rule.matches(None));
+
+        rule.op = RuleOp::Greater;
+        rule.value = AttrValue::Number(2.0);
+        assert!(rule.matches(Some(&AttrValue::Number(3.0))));
+        assert!(rule.matches(Some(&AttrValue::Text("3".to_string()))));
+        assert!(!rule.matches(Some(&AttrValue::Number(2.0))));
+
+        rule.op = RuleOp::Equals;
+        rule.value = AttrValue::Text("a".to_string());
+        assert!(rule.matches(Some(&AttrValue::Text("a".to_string()))));
+        assert!(!rule.matches(Some(&AttrValue::Bool(true))));
+    }
+
+    #[test]
+    fn overrides_win() {
+        let base = NodeStyle { fill: Some([1.0, 0.0, 0.0, 1.0]), radius: Some(4.0), ..NodeStyle::default() };
+        let own = NodeStyle { radius: Some(8.0), ..NodeStyle::default() };
+        let style = base.with(&own);
+
+        assert_eq!(style.fill, Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(style.radius, Some(8.0));
+        assert!(NodeStyle::default().is_empty() && !style.is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+use macroquad::math::Vec2;
+use crate::{Graph, Node, Edge, NodeKey, EdgeKey};
+
+
+
+// nodes at `points` and an edge for every index pair, `undirected` adds the
+// opposite edges as well, the returned edge keys are those of the pairs
+pub fn test_graph(points: &[(f32, f32)], pairs: &[(usize, usize)], undirected: bool) -> (Graph, Vec<NodeKey>, Vec<EdgeKey>) {
+    let mut graph = Graph::new();
+    let n = points
+        .iter()
+        .map(|&(x, y)| graph.add_node(Node::from(Vec2::new(x, y))))
+        .collect::<Vec<NodeKey>>();
+
+    let mut e = Vec::new();
+    for &(a, b) in pairs {
+        let (va, vb) = (Vec2::from(points[a]), Vec2::from(points[b]));
+        e.push(graph.add_edge(Edge::from(va, vb), n[a], n[b]));
+        if undirected { graph.add_edge(Edge::from(vb, va), n[b], n[a]); }
+    }
+    (graph, n, e)
+}
+
+// `n` nodes 10 apart on the x axis, each joined to the next in both directions
+pub fn path_graph(n: usize) -> (Graph, Vec<NodeKey>, Vec<EdgeKey>) {
+    let points = (0..n).map(|i| (i as f32 * 10.0, 0.0)).collect::<Vec<(f32, f32)>>();
+    let pairs = (1..n).map(|i| (i - 1, i)).collect::<Vec<(usize, usize)>>();
+    test_graph(&points, &pairs, true)
+}
+
+// an edge goes from `a` to `b`
+pub fn joined(graph: &Graph, a: NodeKey, b: NodeKey) -> bool {
+    graph.successors(a).iter().any(|&(k, _e)| k == b)
+}
@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+use macroquad::color::*;
+use slotmap::SecondaryMap;
+use crate::{Graph, NodeKey, EdgeKey, NodeGetSet, EdgeGetSet, HashSet, NodeShape, draw_node_shape, draw_node_outline, DIM_ALPHA, to_color, EDGE_THICKNESS, NODE_OUTLINE_THICKNESS};
+
+
+
+pub const TRAVERSAL_EDGE_COLOR: Color = GOLD;
+pub const TRAVERSAL_EDGE_THICKNESS: f32 = 5.0;
+pub const TRAVERSAL_ROOT_RADIUS: f32 = 14.0;
+pub const TRAVERSAL_ROOT_COLOR: Color = WHITE;
+// hue of the deepest level, the root is red
+pub const LEVEL_HUE_RANGE: f32 = 0.75;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum TraversalKind {Bfs, Dfs}
+
+
+pub struct Traversal {
+    pub root: NodeKey,
+    // nodes in the order they were discovered
+    pub order: Vec<NodeKey>,
+    pub depth: SecondaryMap<NodeKey, usize>,
+    // edge a node was discovered through, the root has none
+    pub parents: SecondaryMap<NodeKey, EdgeKey>
+}
+
+
+impl Traversal {
+    fn new(root: NodeKey) -> Self {
+        let mut depth = SecondaryMap::new();
+        depth.insert(root, 0);
+        Self {
+            root,
+            order: vec![root],
+            depth,
+            parents: SecondaryMap::new()
+        }
+    }
+
+    fn discover(&mut self, key: NodeKey, parent: NodeKey, e_k: EdgeKey) {
+        let d = self.depth[parent] + 1;
+        self.depth.insert(key, d);
+        self.parents.insert(key, e_k);
+        self.order.push(key);
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.depth.values().copied().max().unwrap_or(0)
+    }
+
+    pub fn draw(&self, graph: &Graph) {
+        for (_n_k, &e_k) in &self.parents {
+            if let Some(e) = graph.edges.get(e_k) {
+                e.draw_selected(EDGE_THICKNESS, TRAVERSAL_EDGE_THICKNESS, TRAVERSAL_EDGE_COLOR);
+            }
+        }
+        if let Some(n) = graph.nodes.get(self.root) {
+            n.draw(TRAVERSAL_ROOT_RADIUS, TRAVERSAL_ROOT_COLOR);
+        }
+    }
+
+    // fills the nodes with a hue per depth over their regular fill, meant for a BFS traversal
+    // so the hues are levels, `filter` dims the other nodes like `Graph::draw_nodes`
+    pub fn draw_levels(&self, graph: &Graph, filter: Option<&HashSet<NodeKey>>) {
+        let max = self.max_depth().max(1) as f32;
+        for (n_k, &d) in &self.depth {
+            let Some(n) = graph.nodes.get(n_k) else { continue; };
+            let alpha = match filter {
+                Some(keys) if !keys.contains(&n_k) => DIM_ALPHA,
+                _ => 1.0
+            };
+            let s = n.get_resolved_style();
+            let v = n.get_p_v();
+            let r = n.get_radius();
+            let shape = s.shape.unwrap_or(NodeShape::Circle);
+
+            let mut col = hsl_to_rgb(d as f32 / max * LEVEL_HUE_RANGE, 1.0, 0.5);
+            col.a = alpha;
+            draw_node_shape(v, shape, r, col);
+            if let Some(outline) = s.outline {
+                let mut col = to_color(outline);
+                col.a *= alpha;
+                draw_node_outline(v, shape, r, s.outline_thickness.unwrap_or(NODE_OUTLINE_THICKNESS), col);
+            }
+        }
+    }
+}
+
+
+
+impl Graph {
+    // neighbours through outgoing edges, and incoming ones too when `directed` is false
+    fn adjacent(&self, key: NodeKey, directed: bool) -> Vec<(NodeKey, EdgeKey)> {
+        let mut adjacent = self.successors(key);
+        if !directed {
+            adjacent.extend(self.nodes[key].get_keys().heads
+                .iter()
+                .map(|&e_k| (self.edges[e_k].get_keys().from, e_k)));
+        }
+        adjacent
+    }
+
+    pub fn bfs(&self, root: NodeKey, directed: bool) -> Traversal {
+        let mut t = Traversal::new(root);
+        let mut queue = VecDeque::from([root]);
+
+        while let Some(n_k) = queue.pop_front() {
+            for (nn_k, e_k) in self.adjacent(n_k, directed) {
+                if t.depth.contains_key(nn_k) { continue; }
+                t.discover(nn_k, n_k, e_k);
+                queue.push_back(nn_k);
+            }
+        }
+        t
+    }
+
+    // preorder, a node is explored completely before its next sibling
+    pub fn dfs(&self, root: NodeKey, directed: bool) -> Traversal {
+        let mut t = Traversal::new(root);
+        let mut stack = vec![(root, self.adjacent(root, directed), 0)];
+
+        while let Some((n_k, adjacent, i)) = stack.last_mut() {
+            let n_k = *n_k;
+            match adjacent.get(*i).copied() {
+                Some((nn_k, e_k)) => {
+                    *i += 1;
+                    if t.depth.contains_key(nn_k) { continue; }
+                    t.discover(nn_k, n_k, e_k);
+                    stack.push((nn_k, self.adjacent(nn_k, directed), 0));
+                }
+                None => { stack.pop(); }
+            }
+        }
+        t
+    }
+
+    pub fn traverse(&self, root: NodeKey, kind: TraversalKind, directed: bool) -> Traversal {
+        match kind {
+            TraversalKind::Bfs => self.bfs(root, directed),
+            TraversalKind::Dfs => self.dfs(root, directed)
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use super::*;
+
+    // a -> b -> d and a -> c, d is one level deeper than c
+    fn graph() -> (Graph, Vec<NodeKey>, Vec<EdgeKey>) {
+        test_graph(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0), (30.0, 0.0)], &[(0, 1), (0, 2), (1, 3)], false)
+    }
+
+    #[test]
+    fn bfs_levels() {
+        let (graph, n, e) = graph();
+        let t = graph.bfs(n[0], true);
+
+        assert_eq!(t.order.len(), 4);
+        assert_eq!(t.order[0], n[0]);
+        assert_eq!(t.order[3], n[3]);
+        assert_eq!(n.iter().map(|&k| t.depth[k]).collect::<Vec<_>>(), [0, 1, 1, 2]);
+        assert_eq!(t.max_depth(), 2);
+
+        assert!(!t.parents.contains_key(n[0]));
+        assert_eq!([1, 2, 3].map(|i| t.parents[n[i]]), e[..]);
+    }
+
+    #[test]
+    fn dfs_goes_deep_first() {
+        let (graph, n, e) = graph();
+        let t = graph.dfs(n[0], true);
+        let pos = |k: NodeKey| t.order.iter().position(|&o| o == k).unwrap();
+
+        assert_eq!(t.order.len(), 4);
+        assert_eq!(t.order[0], n[0]);
+        // d is reached right after b, before or after c
+        assert_eq!(pos(n[3]), pos(n[1]) + 1);
+        assert_eq!(t.depth[n[3]], 2);
+        assert_eq!(t.parents[n[3]], e[2]);
+    }
+
+    #[test]
+    fn dfs_depth_is_tree_depth() {
+        // a triangle: bfs puts both neighbours on level 1, dfs walks a -> b -> c
+        let (graph, n, _e) = test_graph(&[(0.0, 0.0), (10.0, 10.0), (20.0, 20.0)], &[(0, 1), (1, 2), (0, 2)], true);
+
+        assert_eq!(graph.bfs(n[0], false).max_depth(), 1);
+        assert_eq!(graph.dfs(n[0], false).max_depth(), 2);
+    }
+
+    #[test]
+    fn edge_directions() {
+        let (graph, n, e) = graph();
+
+        let t = graph.bfs(n[3], true);
+        assert_eq!(t.order, vec![n[3]]);
+
+        // against the edges d reaches everything, c through a
+        let t = graph.bfs(n[3], false);
+        assert_eq!(t.order.len(), 4);
+        assert_eq!(n.iter().map(|&k| t.depth[k]).collect::<Vec<_>>(), [2, 1, 3, 0]);
+        assert_eq!(t.parents[n[2]], e[1]);
+
+        let t = graph.traverse(n[3], TraversalKind::Dfs, false);
+        assert_eq!(t.order, vec![n[3], n[1], n[0], n[2]]);
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Serialize, Deserialize};
+
+
+
+pub const LENGTH_PRECISION: usize = 1;
+pub const AUTO_KM_THRESHOLD: f32 = 1000.0;
+
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum LengthUnit {Auto, Meters, Kilometers, Miles, Feet}
+
+impl LengthUnit {
+    pub const ALL: [LengthUnit; 5] = [LengthUnit::Auto, LengthUnit::Meters, LengthUnit::Kilometers, LengthUnit::Miles, LengthUnit::Feet];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LengthUnit::Auto => "m / km",
+            LengthUnit::Meters => "m",
+            LengthUnit::Kilometers => "km",
+            LengthUnit::Miles => "mi",
+            LengthUnit::Feet => "ft"
+        }
+    }
+
+    pub fn meters(&self) -> f32 {
+        match self {
+            LengthUnit::Auto | LengthUnit::Meters => 1.0,
+            LengthUnit::Kilometers => 1000.0,
+            LengthUnit::Miles => 1609.344,
+            LengthUnit::Feet => 0.3048
+        }
+    }
+}
+
+
+// how lengths are shown, real units are used only once the map is calibrated
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Units {
+    pub unit: LengthUnit,
+    pub precision: usize
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self {
+            unit: LengthUnit::Auto,
+            precision: LENGTH_PRECISION
+        }
+    }
+}
+
+impl Units {
+    pub fn format(&self, length: f32, meters_per_unit: Option<f32>) -> String {
+        let Some(k) = meters_per_unit else {
+            return format!("{:.0}", length);
+        };
+        let m = length * k;
+        let unit = match self.unit {
+            LengthUnit::Auto if m.abs() >= AUTO_KM_THRESHOLD => LengthUnit::Kilometers,
+            LengthUnit::Auto => LengthUnit::Meters,
+            u => u
+        };
+        format!("{:.*} {}", self.precision, m / unit.meters(), unit.name())
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncalibrated() {
+        assert_eq!(Units::default().format(1234.4, None), "1234");
+    }
+
+    #[test]
+    fn auto_switches_to_kilometers() {
+        let units = Units::default();
+        assert_eq!(units.format(12.34, Some(1.0)), "12.3 m");
+        assert_eq!(units.format(999.0, Some(1.0)), "999.0 m");
+        assert_eq!(units.format(150.0, Some(10.0)), "1.5 km");
+    }
+
+    #[test]
+    fn fixed_units() {
+        let feet = Units { unit: LengthUnit::Feet, precision: 0 };
+        assert_eq!(feet.format(10.0, Some(0.3048)), "10 ft");
+
+        let miles = Units { unit: LengthUnit::Miles, precision: 2 };
+        assert_eq!(miles.format(1609.344, Some(1.0)), "1.00 mi");
+
+        let km = Units { unit: LengthUnit::Kilometers, precision: 3 };
+        assert_eq!(km.format(5.0, Some(2.0)), "0.010 km");
+    }
+}
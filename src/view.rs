@@ -0,0 +1,82 @@
+use macroquad::camera::Camera2D;
+use macroquad::math::Vec2;
+use macroquad::window::{screen_width, screen_height};
+use crate::{Graph, NodeKey, NodeGetSet};
+
+
+
+pub const ZOOM_STEP: f32 = 1.02;
+pub const KEY_ZOOM_STEP: f32 = 1.25;
+pub const FIT_MARGIN: f32 = 40.0;
+pub const MIN_ZOOM: f32 = 1e-5;
+pub const MAX_ZOOM: f32 = 1.0;
+
+
+// keeps the world point under `screen_v` in place
+pub fn zoom_at(cam: &mut Camera2D, factor: f32, screen_v: Vec2) {
+    let before = cam.screen_to_world(screen_v);
+    let zoom = cam.zoom * factor;
+    if zoom.x.abs() < MIN_ZOOM || zoom.x.abs() > MAX_ZOOM { return; }
+
+    cam.zoom = zoom;
+    let after = cam.screen_to_world(screen_v);
+    cam.target += before - after;
+}
+
+
+// node centers grown by the drawn node radii
+pub fn nodes_bounds(graph: &Graph, keys: &[NodeKey]) -> Option<(Vec2, Vec2)> {
+    keys.iter()
+        .map(|&n_k| {
+            let n = &graph.nodes[n_k];
+            let r = Vec2::splat(n.get_radius());
+            (n.get_p_v() - r, n.get_p_v() + r)
+        })
+        .reduce(|(min, max), (a, b)| (min.min(a), max.max(b)))
+}
+
+
+// centers the rectangle and zooms so it fills the window less a margin on every side
+pub fn fit_rect(cam: &mut Camera2D, min: Vec2, max: Vec2) {
+    let (w, h) = (screen_width(), screen_height());
+    let size = (max - min).max(Vec2::ONE);
+    let scale = ((w - 2.0 * FIT_MARGIN).max(1.0) / size.x).min((h - 2.0 * FIT_MARGIN).max(1.0) / size.y);
+
+    // the y axis of the camera is flipped
+    let sign_y = if cam.zoom.y < 0.0 { -1.0 } else { 1.0 };
+    cam.target = (min + max) / 2.0;
+    cam.zoom = Vec2::new(2.0 * scale / w, sign_y * 2.0 * scale / h);
+}
+
+
+pub fn fit_nodes(cam: &mut Camera2D, graph: &Graph, keys: &[NodeKey]) -> bool {
+    match nodes_bounds(graph, keys) {
+        Some((min, max)) => {
+            fit_rect(cam, min, max);
+            true
+        }
+        None => false
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::NodeStyle;
+    use crate::testing::*;
+    use super::*;
+
+    #[test]
+    fn bounds_include_the_radii() {
+        let (mut graph, n, _e) = test_graph(&[(0.0, 0.0), (30.0, -10.0), (10.0, 20.0)], &[], false);
+        for &n_k in &n {
+            graph.set_node_style(n_k, NodeStyle { radius: Some(5.0), ..NodeStyle::default() });
+        }
+        graph.refresh_styles();
+
+        assert_eq!(nodes_bounds(&graph, &n), Some((Vec2::new(-5.0, -15.0), Vec2::new(35.0, 25.0))));
+        assert_eq!(nodes_bounds(&graph, &n[..1]), Some((Vec2::splat(-5.0), Vec2::splat(5.0))));
+        assert_eq!(nodes_bounds(&graph, &[]), None);
+    }
+}
@@ -0,0 +1,401 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use macroquad::color::*;
+use macroquad::text::{draw_text_ex, TextParams};
+use slotmap::SecondaryMap;
+use crate::{Graph, NodeKey, EdgeKey, NodeGetSet, HashSet, EDGE_THICKNESS, FONT_SIZE};
+
+
+
+pub const ANIMATION_SPEED: f32 = 4.0;
+pub const VISITED_COLOR: Color = Color::new(0.45, 0.45, 0.45, 1.0);
+pub const FRONTIER_COLOR: Color = SKYBLUE;
+pub const CURRENT_COLOR: Color = RED;
+pub const TREE_EDGE_COLOR: Color = GOLD;
+pub const RELAXED_EDGE_COLOR: Color = ORANGE;
+pub const ANIMATION_NODE_RADIUS: f32 = 14.0;
+pub const ANIMATION_EDGE_THICKNESS: f32 = 5.0;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Algorithm {Dijkstra, AStar, Bfs, Dfs}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 4] = [Algorithm::Dijkstra, Algorithm::AStar, Algorithm::Bfs, Algorithm::Dfs];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Dijkstra => "Dijkstra",
+            Algorithm::AStar => "A*",
+            Algorithm::Bfs => "BFS",
+            Algorithm::Dfs => "DFS"
+        }
+    }
+}
+
+
+// state of the search after a node has been expanded
+#[derive(Clone)]
+pub struct AlgoStep {
+    pub current: Option<NodeKey>,
+    pub frontier: HashSet<NodeKey>,
+    pub visited: HashSet<NodeKey>,
+    // tentative distances along the discovered parent edges
+    pub dist: SecondaryMap<NodeKey, f32>,
+    pub parents: SecondaryMap<NodeKey, EdgeKey>,
+    // edges looked at while expanding `current`
+    pub relaxed: Vec<EdgeKey>,
+    pub finished: bool
+}
+
+
+
+#[derive(PartialEq)]
+struct Pending(f32, NodeKey);
+
+impl Eq for Pending {}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+
+enum Frontier {
+    Heap(BinaryHeap<Pending>),
+    Queue(VecDeque<NodeKey>),
+    Stack(Vec<NodeKey>)
+}
+
+impl Frontier {
+    fn push(&mut self, priority: f32, key: NodeKey) {
+        match self {
+            Frontier::Heap(h) => h.push(Pending(priority, key)),
+            Frontier::Queue(q) => q.push_back(key),
+            Frontier::Stack(s) => s.push(key)
+        }
+    }
+
+    fn pop(&mut self) -> Option<NodeKey> {
+        match self {
+            Frontier::Heap(h) => h.pop().map(|p| p.1),
+            Frontier::Queue(q) => q.pop_front(),
+            Frontier::Stack(s) => s.pop()
+        }
+    }
+}
+
+
+// runs the search from `start` and keeps a snapshot of every expansion,
+// it stops when `finish` is expanded or nothing is left to expand,
+// distances use the numeric edge attribute `weight` when given
+pub fn record_steps(graph: &Graph, algorithm: Algorithm, start: NodeKey, finish: Option<NodeKey>, weight: Option<&str>) -> Vec<AlgoStep> {
+    // the straight line distance only bounds lengths, not arbitrary weights
+    let goal_v = finish
+        .filter(|_| weight.is_none())
+        .map(|f_k| graph.nodes[f_k].get_p_v());
+    let heuristic = |n_k: NodeKey| match (algorithm, goal_v) {
+        (Algorithm::AStar, Some(v)) => graph.nodes[n_k].get_p_v().distance(v),
+        _ => 0.0
+    };
+
+    let mut frontier = match algorithm {
+        Algorithm::Dijkstra | Algorithm::AStar => Frontier::Heap(BinaryHeap::new()),
+        Algorithm::Bfs => Frontier::Queue(VecDeque::new()),
+        Algorithm::Dfs => Frontier::Stack(Vec::new())
+    };
+    let mut state = AlgoStep {
+        current: None,
+        frontier: HashSet::new(),
+        visited: HashSet::new(),
+        dist: SecondaryMap::new(),
+        parents: SecondaryMap::new(),
+        relaxed: Vec::new(),
+        finished: false
+    };
+
+    state.dist.insert(start, 0.0);
+    state.frontier.insert(start);
+    frontier.push(heuristic(start), start);
+    let mut steps = vec![state.clone()];
+
+    while let Some(n_k) = frontier.pop() {
+        // stale heap entries and nodes pushed more than once
+        if state.visited.contains(&n_k) { continue; }
+
+        state.current = Some(n_k);
+        state.visited.insert(n_k);
+        state.frontier.remove(&n_k);
+        state.relaxed.clear();
+
+        if Some(n_k) == finish {
+            state.finished = true;
+            steps.push(state.clone());
+            break;
+        }
+
+        let successors = match algorithm {
+            // reversed so the stack expands neighbours in their natural order
+            Algorithm::Dfs => graph.successors(n_k).into_iter().rev().collect(),
+            _ => graph.successors(n_k)
+        };
+        for (nn_k, e_k) in successors {
+            if state.visited.contains(&nn_k) { continue; }
+            state.relaxed.push(e_k);
+
+            let nd = state.dist[n_k] + graph.edge_weight(e_k, weight).max(0.0);
+            let improves = match algorithm {
+                Algorithm::Dijkstra | Algorithm::AStar => match state.dist.get(nn_k) {
+                    Some(&od) => nd < od,
+                    None => true
+                },
+                Algorithm::Bfs => !state.frontier.contains(&nn_k),
+                Algorithm::Dfs => true
+            };
+            if improves {
+                state.dist.insert(nn_k, nd);
+                state.parents.insert(nn_k, e_k);
+                state.frontier.insert(nn_k);
+                frontier.push(nd + heuristic(nn_k), nn_k);
+            }
+        }
+        steps.push(state.clone());
+    }
+    steps
+}
+
+
+
+pub struct AlgoPlayer {
+    pub algorithm: Algorithm,
+    pub steps: Vec<AlgoStep>,
+    pub index: usize,
+    pub playing: bool,
+    // steps per second
+    pub speed: f32,
+    // edge attribute the steps were recorded with, lengths otherwise
+    pub weight: Option<String>,
+    // nodes the steps were recorded between
+    pub start: Option<NodeKey>,
+    pub finish: Option<NodeKey>,
+    timer: f32,
+    revision: u64
+}
+
+
+impl AlgoPlayer {
+    pub fn new() -> Self {
+        Self {
+            algorithm: Algorithm::Dijkstra,
+            steps: Vec::new(),
+            index: 0,
+            playing: false,
+            speed: ANIMATION_SPEED,
+            weight: None,
+            start: None,
+            finish: None,
+            timer: 0.0,
+            revision: 0
+        }
+    }
+
+    pub fn record(&mut self, graph: &Graph, start: NodeKey, finish: Option<NodeKey>, weight: Option<&str>) {
+        self.steps = record_steps(graph, self.algorithm, start, finish, weight);
+        self.weight = weight.map(|w| w.to_string());
+        self.start = Some(start);
+        self.finish = finish;
+        self.index = 0;
+        self.timer = 0.0;
+        self.revision = graph.revision();
+    }
+
+    pub fn clear(&mut self) {
+        self.steps.clear();
+        self.index = 0;
+        self.playing = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.steps.is_empty()
+    }
+
+    // the recorded keys are meaningless once the graph, the weights or the endpoints have changed
+    pub fn is_outdated(&self, graph: &Graph, weight: Option<&str>, start: Option<NodeKey>, finish: Option<NodeKey>) -> bool {
+        self.is_active() && (self.revision != graph.revision() || self.weight.as_deref() != weight
+            || self.start != start || self.finish != finish)
+    }
+
+    pub fn current(&self) -> Option<&AlgoStep> {
+        self.steps.get(self.index)
+    }
+
+    // weighted distances are not lengths
+    pub fn format_dist(&self, graph: &Graph, d: f32) -> String {
+        match self.weight {
+            Some(_) => format!("{:.1}", d),
+            None => graph.format_length(d)
+        }
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.index + 1 >= self.steps.len()
+    }
+
+    pub fn step_forward(&mut self) {
+        if !self.is_at_end() { self.index += 1; }
+    }
+
+    pub fn step_back(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if !self.playing { return; }
+
+        self.timer += dt * self.speed;
+        while self.timer >= 1.0 {
+            self.timer -= 1.0;
+            self.step_forward();
+        }
+        if self.is_at_end() { self.playing = false; }
+    }
+
+    pub fn draw(&self, graph: &Graph) {
+        let Some(step) = self.current() else { return; };
+
+        for (_n_k, &e_k) in &step.parents {
+            if let Some(e) = graph.edges.get(e_k) {
+                e.draw_selected(EDGE_THICKNESS, ANIMATION_EDGE_THICKNESS, TREE_EDGE_COLOR);
+            }
+        }
+        for &e_k in &step.relaxed {
+            if let Some(e) = graph.edges.get(e_k) {
+                e.draw_selected(EDGE_THICKNESS, ANIMATION_EDGE_THICKNESS, RELAXED_EDGE_COLOR);
+            }
+        }
+
+        let draw_set = |keys: &HashSet<NodeKey>, col: Color| {
+            for &n_k in keys {
+                if let Some(n) = graph.nodes.get(n_k) {
+                    n.draw(ANIMATION_NODE_RADIUS, col);
+                }
+            }
+        };
+        draw_set(&step.visited, VISITED_COLOR);
+        draw_set(&step.frontier, FRONTIER_COLOR);
+        if let Some(n) = step.current.and_then(|n_k| graph.nodes.get(n_k)) {
+            n.draw(ANIMATION_NODE_RADIUS, CURRENT_COLOR);
+        }
+
+        for (n_k, &d) in &step.dist {
+            if let Some(n) = graph.nodes.get(n_k) {
+                let v = n.get_p_v();
+                draw_text_ex(&self.format_dist(graph, d), v.x + ANIMATION_NODE_RADIUS, v.y - ANIMATION_NODE_RADIUS, TextParams {
+                    font_size: FONT_SIZE as u16,
+                    color: BLACK,
+                    ..TextParams::default()
+                });
+            }
+        }
+    }
+}
+
+impl Default for AlgoPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::AttrValue;
+    use crate::testing::*;
+    use super::*;
+
+    // a -> b directly is short but costly, a -> c -> b is cheap, b -> d ends the chain
+    fn graph() -> (Graph, Vec<NodeKey>, Vec<EdgeKey>) {
+        let points = [(0.0, 0.0), (100.0, 0.0), (50.0, 10.0), (200.0, 0.0)];
+        let (mut graph, n, e) = test_graph(&points, &[(0, 1), (0, 2), (2, 1), (1, 3)], false);
+        for (&e_k, cost) in e.iter().zip([10.0, 1.0, 1.0, 1.0]) {
+            graph.set_edge_attr(e_k, "cost", AttrValue::Number(cost));
+        }
+        (graph, n, e)
+    }
+
+    #[test]
+    fn dijkstra_by_length() {
+        let (graph, n, e) = graph();
+        let steps = record_steps(&graph, Algorithm::Dijkstra, n[0], Some(n[1]), None);
+        let last = steps.last().unwrap();
+
+        assert!(last.finished);
+        assert_eq!(last.current, Some(n[1]));
+        assert_eq!(last.dist[n[1]], 100.0);
+        assert_eq!(last.parents[n[1]], e[0]);
+    }
+
+    #[test]
+    fn dijkstra_and_astar_by_weight() {
+        let (graph, n, e) = graph();
+        for algorithm in [Algorithm::Dijkstra, Algorithm::AStar] {
+            let steps = record_steps(&graph, algorithm, n[0], Some(n[1]), Some("cost"));
+            let last = steps.last().unwrap();
+
+            assert!(last.finished);
+            assert_eq!(last.dist[n[1]], 2.0);
+            assert_eq!(last.parents[n[1]], e[2]);
+        }
+    }
+
+    #[test]
+    fn one_step_per_expansion() {
+        let (graph, n, _e) = graph();
+        for algorithm in Algorithm::ALL {
+            let steps = record_steps(&graph, algorithm, n[0], None, None);
+
+            // the initial state and one step per reachable node
+            assert_eq!(steps.len(), 5);
+            assert_eq!(steps[0].current, None);
+            assert_eq!(steps[0].frontier.len(), 1);
+            assert_eq!(steps[4].visited.len(), 4);
+            assert!(steps.iter().all(|s| !s.finished));
+        }
+    }
+
+    #[test]
+    fn unreachable_finish() {
+        let (graph, n, _e) = graph();
+        let steps = record_steps(&graph, Algorithm::Bfs, n[3], Some(n[0]), None);
+
+        assert_eq!(steps.len(), 2);
+        assert!(!steps[1].finished);
+    }
+
+    #[test]
+    fn player_steps() {
+        let (graph, n, _e) = graph();
+        let mut player = AlgoPlayer::new();
+        player.record(&graph, n[0], Some(n[3]), Some("cost"));
+
+        let (start, finish) = (Some(n[0]), Some(n[3]));
+        assert!(!player.is_outdated(&graph, Some("cost"), start, finish));
+        assert!(player.is_outdated(&graph, None, start, finish));
+        assert!(player.is_outdated(&graph, Some("cost"), start, None));
+        assert!(player.is_outdated(&graph, Some("cost"), Some(n[1]), finish));
+        assert_eq!(player.finish, finish);
+
+        player.playing = true;
+        player.update(100.0);
+        assert!(player.is_at_end() && !player.playing);
+        assert_eq!(player.current().unwrap().dist[n[3]], 3.0);
+    }
+}